@@ -0,0 +1,714 @@
+//! Compact binary encoding of LSAG and SAG signatures, on any supported curve.
+//!
+//! Layout (all integers, including the scalars, are big endian):
+//!
+//! ```text
+//! version (1) | scheme (1) | curve (1) | hash (1) | flags (1)
+//! ring size n (2) | ring: n compressed points (P each)
+//! c0 (32) | responses: n scalars (32 each)
+//! LSAG only: key image (P) | linkability flag length (4) | linkability flag
+//! message length (4) | message
+//! if FLAG_EVM_WITNESSES: witness count (2) | for each witness: length (4) | witness
+//! ```
+//!
+//! Points are written with the encoding of their curve: `P` is 33 bytes on secp256k1 and
+//! P-256, and 32 bytes on ristretto255 and ed25519.
+use crate::lsag_verifier::{verify_lsag, GenericLsag, Lsag};
+use crate::sag_verifier::{verify_sag, GenericSag, Sag};
+use crate::utils::curve::{Curve, RingCurve};
+use crate::utils::signature_config::{HashFunction, SignatureConfig};
+use k256::{AffinePoint, Secp256k1};
+
+/// Version of the binary format written by `to_bytes`
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// Size of a scalar
+const SCALAR_SIZE: usize = 32;
+
+/// The signature has a config
+pub const FLAG_CONFIG: u8 = 0b0000_0001;
+/// The config has an `evmCompatibility` field
+pub const FLAG_EVM_COMPATIBILITY_SET: u8 = 0b0000_0010;
+/// Value of the `evmCompatibility` field
+pub const FLAG_EVM_COMPATIBILITY: u8 = 0b0000_0100;
+/// The signature has `evmWitnesses`
+pub const FLAG_EVM_WITNESSES: u8 = 0b0000_1000;
+/// The signature has a linkability flag (LSAG only)
+pub const FLAG_LINKABILITY_FLAG: u8 = 0b0001_0000;
+
+/// The ring signature schemes supported by the binary format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    Lsag = 1,
+    Sag = 2,
+}
+
+impl TryFrom<u8> for Scheme {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Scheme, String> {
+        match value {
+            1 => Ok(Scheme::Lsag),
+            2 => Ok(Scheme::Sag),
+            _ => Err(format!("Unknown signature scheme: {}", value)),
+        }
+    }
+}
+
+/// Returns the binary identifier of a curve
+pub fn curve_id(curve: Curve) -> u8 {
+    match curve {
+        Curve::Secp256k1 => 1,
+        Curve::P256 => 2,
+        Curve::Ristretto255 => 3,
+        Curve::Ed25519 => 4,
+    }
+}
+
+/// Returns the curve matching a binary identifier
+pub fn curve_from_id(id: u8) -> Result<Curve, String> {
+    match id {
+        1 => Ok(Curve::Secp256k1),
+        2 => Ok(Curve::P256),
+        3 => Ok(Curve::Ristretto255),
+        4 => Ok(Curve::Ed25519),
+        _ => Err(format!("Unknown curve identifier: {}", id)),
+    }
+}
+
+/// Returns the binary identifier of a hash function (0 if not set)
+pub fn hash_id(hash: Option<HashFunction>) -> u8 {
+    match hash {
+        None => 0,
+        Some(HashFunction::Keccak256) => 1,
+        Some(HashFunction::Sha256) => 2,
+        Some(HashFunction::Sha512) => 3,
+    }
+}
+
+/// Returns the hash function matching a binary identifier
+pub fn hash_from_id(id: u8) -> Result<Option<HashFunction>, String> {
+    match id {
+        0 => Ok(None),
+        1 => Ok(Some(HashFunction::Keccak256)),
+        2 => Ok(Some(HashFunction::Sha256)),
+        3 => Ok(Some(HashFunction::Sha512)),
+        _ => Err(format!("Unknown hash function identifier: {}", id)),
+    }
+}
+
+/// A LSAG or SAG signature on secp256k1
+#[derive(Clone, Debug, PartialEq)]
+pub enum RingSignature {
+    Lsag(Lsag),
+    Sag(Sag),
+}
+
+impl RingSignature {
+    /// Returns the scheme of the signature
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            RingSignature::Lsag(_) => Scheme::Lsag,
+            RingSignature::Sag(_) => Scheme::Sag,
+        }
+    }
+
+    /// Returns the ring of the signature
+    pub fn ring(&self) -> &[AffinePoint] {
+        match self {
+            RingSignature::Lsag(signature) => &signature.ring,
+            RingSignature::Sag(signature) => &signature.ring,
+        }
+    }
+
+    /// Returns the curve of the signature
+    pub fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
+
+    /// Returns the config of the signature
+    pub fn config(&self) -> Option<&SignatureConfig> {
+        match self {
+            RingSignature::Lsag(signature) => signature.config.as_ref(),
+            RingSignature::Sag(signature) => signature.config.as_ref(),
+        }
+    }
+
+    /// Returns the hash function of the signature. Signatures without config use keccak256.
+    pub fn hash(&self) -> HashFunction {
+        self.config()
+            .and_then(|config| config.hash)
+            .unwrap_or(HashFunction::Keccak256)
+    }
+
+    /// Returns the linkability flag of a LSAG signature
+    pub fn linkability_flag(&self) -> Option<&str> {
+        match self {
+            RingSignature::Lsag(signature) => signature.linkability_flag.as_deref(),
+            RingSignature::Sag(_) => None,
+        }
+    }
+
+    /// Verifies the signature
+    pub fn verify(&self) -> bool {
+        match self {
+            RingSignature::Lsag(signature) => verify_lsag(signature.clone()),
+            RingSignature::Sag(signature) => verify_sag(signature.clone()),
+        }
+    }
+
+    /// Encodes the signature in the binary format.
+    /// Fails if the ring or a string is too long for its length prefix.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        match self {
+            RingSignature::Lsag(signature) => signature.to_bytes(),
+            RingSignature::Sag(signature) => signature.to_bytes(),
+        }
+    }
+
+    /// Decodes a LSAG or SAG signature on secp256k1 from the binary format.
+    /// Signatures on the other curves are decoded with `GenericLsag::from_bytes` and
+    /// `GenericSag::from_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<RingSignature, String> {
+        let mut reader = Reader::new(bytes);
+        let header = Header::read(&mut reader)?;
+        header.check_curve::<Secp256k1>()?;
+
+        let signature = match header.scheme {
+            Scheme::Lsag => RingSignature::Lsag(read_lsag(&header, &mut reader)?),
+            Scheme::Sag => RingSignature::Sag(read_sag(&header, &mut reader)?),
+        };
+        reader.finish()?;
+
+        Ok(signature)
+    }
+
+    /// Parses a signature from the JSON format of the TS libraries.
+    /// Signatures with a `keyImage` field are parsed as LSAG, the others as SAG.
+    pub fn from_json(json: &str) -> Result<RingSignature, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        if value.get("keyImage").is_some() {
+            Ok(RingSignature::Lsag(Lsag::from_json(json)?))
+        } else {
+            Ok(RingSignature::Sag(Sag::from_json(json)?))
+        }
+    }
+
+    /// Converts the signature to the JSON format of the TS libraries
+    pub fn to_json(&self) -> String {
+        match self {
+            RingSignature::Lsag(signature) => signature.to_json(),
+            RingSignature::Sag(signature) => signature.to_json(),
+        }
+    }
+}
+
+/// Converts a JSON signature to the binary format
+pub fn json_to_bytes(json: &str) -> Result<Vec<u8>, String> {
+    RingSignature::from_json(json)?.to_bytes()
+}
+
+/// Converts a binary signature to the JSON format
+pub fn bytes_to_json(bytes: &[u8]) -> Result<String, String> {
+    Ok(RingSignature::from_bytes(bytes)?.to_json())
+}
+
+impl<C: RingCurve> GenericLsag<C> {
+    /// Encodes the signature in the binary format.
+    /// Fails if the ring or a string is too long for its length prefix.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut flags = config_flags(&self.config);
+        if self.evm_witnesses.is_some() {
+            flags |= FLAG_EVM_WITNESSES;
+        }
+        if self.linkability_flag.is_some() {
+            flags |= FLAG_LINKABILITY_FLAG;
+        }
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, Scheme::Lsag, C::CURVE, &self.config, flags);
+        write_ring_and_scalars::<C>(&mut bytes, &self.ring, &self.c0, &self.responses)?;
+        write_point::<C>(&mut bytes, &self.key_image);
+        write_string(
+            &mut bytes,
+            self.linkability_flag.as_deref().unwrap_or_default(),
+        )?;
+        write_string(&mut bytes, &self.message)?;
+        if let Some(witnesses) = &self.evm_witnesses {
+            let count = u16::try_from(witnesses.len())
+                .map_err(|_| "Too many EVM witnesses for the binary format".to_string())?;
+            bytes.extend_from_slice(&count.to_be_bytes());
+            for witness in witnesses {
+                write_string(&mut bytes, witness)?;
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decodes a LSAG signature from the binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<GenericLsag<C>, String> {
+        let mut reader = Reader::new(bytes);
+        let header = Header::read(&mut reader)?;
+        if header.scheme != Scheme::Lsag {
+            return Err("Expected a LSAG signature, got a SAG".to_string());
+        }
+        header.check_curve::<C>()?;
+
+        let signature = read_lsag(&header, &mut reader)?;
+        reader.finish()?;
+
+        Ok(signature)
+    }
+}
+
+impl<C: RingCurve> GenericSag<C> {
+    /// Encodes the signature in the binary format.
+    /// Fails if the ring or the message is too long for its length prefix.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let flags = config_flags(&self.config);
+
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, Scheme::Sag, C::CURVE, &self.config, flags);
+        write_ring_and_scalars::<C>(&mut bytes, &self.ring, &self.c0, &self.responses)?;
+        write_string(&mut bytes, &self.message)?;
+
+        Ok(bytes)
+    }
+
+    /// Decodes a SAG signature from the binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<GenericSag<C>, String> {
+        let mut reader = Reader::new(bytes);
+        let header = Header::read(&mut reader)?;
+        if header.scheme != Scheme::Sag {
+            return Err("Expected a SAG signature, got a LSAG".to_string());
+        }
+        header.check_curve::<C>()?;
+
+        let signature = read_sag(&header, &mut reader)?;
+        reader.finish()?;
+
+        Ok(signature)
+    }
+}
+
+/// The decoded fixed-size header
+struct Header {
+    scheme: Scheme,
+    curve: Curve,
+    config: Option<SignatureConfig>,
+    flags: u8,
+}
+
+impl Header {
+    fn read(reader: &mut Reader) -> Result<Header, String> {
+        let version = reader.read_u8()?;
+        if version != BINARY_FORMAT_VERSION {
+            return Err(format!("Unsupported binary format version: {}", version));
+        }
+        let scheme = Scheme::try_from(reader.read_u8()?)?;
+        let curve = curve_from_id(reader.read_u8()?)?;
+        let hash = hash_from_id(reader.read_u8()?)?;
+        let flags = reader.read_u8()?;
+
+        let config = if flags & FLAG_CONFIG != 0 {
+            Some(SignatureConfig {
+                evm_compatibility: if flags & FLAG_EVM_COMPATIBILITY_SET != 0 {
+                    Some(flags & FLAG_EVM_COMPATIBILITY != 0)
+                } else {
+                    None
+                },
+                hash,
+            })
+        } else {
+            if hash.is_some() {
+                return Err("Hash function set without config".to_string());
+            }
+            None
+        };
+
+        Ok(Header {
+            scheme,
+            curve,
+            config,
+            flags,
+        })
+    }
+
+    /// Checks that the signature is on the curve `C`
+    fn check_curve<C: RingCurve>(&self) -> Result<(), String> {
+        if self.curve != C::CURVE {
+            return Err(format!(
+                "Expected a {} signature, got a {} signature",
+                C::CURVE.name(),
+                self.curve.name()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn config_flags(config: &Option<SignatureConfig>) -> u8 {
+    let mut flags = 0;
+    if let Some(config) = config {
+        flags |= FLAG_CONFIG;
+        if let Some(evm_compatibility) = config.evm_compatibility {
+            flags |= FLAG_EVM_COMPATIBILITY_SET;
+            if evm_compatibility {
+                flags |= FLAG_EVM_COMPATIBILITY;
+            }
+        }
+    }
+    flags
+}
+
+fn write_header(
+    bytes: &mut Vec<u8>,
+    scheme: Scheme,
+    curve: Curve,
+    config: &Option<SignatureConfig>,
+    flags: u8,
+) {
+    bytes.push(BINARY_FORMAT_VERSION);
+    bytes.push(scheme as u8);
+    bytes.push(curve_id(curve));
+    bytes.push(hash_id(config.as_ref().and_then(|config| config.hash)));
+    bytes.push(flags);
+}
+
+pub(crate) fn write_point<C: RingCurve>(bytes: &mut Vec<u8>, point: &C::Point) {
+    bytes.extend(hex::decode(C::serialize_point(point)).unwrap());
+}
+
+fn write_scalar<C: RingCurve>(bytes: &mut Vec<u8>, scalar: &C::Scalar) {
+    let padded = format!(
+        "{:0>width$}",
+        C::scalar_to_hex(scalar),
+        width = 2 * SCALAR_SIZE
+    );
+    bytes.extend(hex::decode(padded).unwrap());
+}
+
+/// Returns the size of a compressed point of the curve `C`
+fn point_size<C: RingCurve>() -> usize {
+    C::serialize_point(&C::generator()).len() / 2
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) -> Result<(), String> {
+    let length = u32::try_from(string.len())
+        .map_err(|_| "String too long for the binary format".to_string())?;
+    bytes.extend_from_slice(&length.to_be_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+    Ok(())
+}
+
+fn write_ring_and_scalars<C: RingCurve>(
+    bytes: &mut Vec<u8>,
+    ring: &[C::Point],
+    c0: &C::Scalar,
+    responses: &[C::Scalar],
+) -> Result<(), String> {
+    // The ring size prefix also counts the responses
+    if responses.len() != ring.len() {
+        return Err(format!(
+            "Ring and responses must have the same length: {} != {}",
+            ring.len(),
+            responses.len()
+        ));
+    }
+    let ring_size = u16::try_from(ring.len())
+        .map_err(|_| format!("Ring too large for the binary format: {}", ring.len()))?;
+    bytes.extend_from_slice(&ring_size.to_be_bytes());
+    for point in ring {
+        write_point::<C>(bytes, point);
+    }
+    write_scalar::<C>(bytes, c0);
+    for response in responses {
+        write_scalar::<C>(bytes, response);
+    }
+    Ok(())
+}
+
+type RingAndScalars<C> = (
+    Vec<<C as RingCurve>::Point>,
+    <C as RingCurve>::Scalar,
+    Vec<<C as RingCurve>::Scalar>,
+);
+
+fn read_ring_and_scalars<C: RingCurve>(reader: &mut Reader) -> Result<RingAndScalars<C>, String> {
+    let ring_size = reader.read_u16()? as usize;
+    if ring_size == 0 {
+        return Err("Ring cannot be empty".to_string());
+    }
+
+    let ring = (0..ring_size)
+        .map(|_| reader.read_point::<C>())
+        .collect::<Result<Vec<C::Point>, String>>()?;
+    let c0 = reader.read_scalar::<C>()?;
+    let responses = (0..ring_size)
+        .map(|_| reader.read_scalar::<C>())
+        .collect::<Result<Vec<C::Scalar>, String>>()?;
+
+    Ok((ring, c0, responses))
+}
+
+fn read_lsag<C: RingCurve>(header: &Header, reader: &mut Reader) -> Result<GenericLsag<C>, String> {
+    let (ring, c0, responses) = read_ring_and_scalars::<C>(reader)?;
+    let key_image = reader.read_point::<C>()?;
+    let linkability_flag = reader.read_string()?;
+    let message = reader.read_string()?;
+
+    let evm_witnesses = if header.flags & FLAG_EVM_WITNESSES != 0 {
+        let count = reader.read_u16()?;
+        Some(
+            (0..count)
+                .map(|_| reader.read_string())
+                .collect::<Result<Vec<String>, String>>()?,
+        )
+    } else {
+        None
+    };
+
+    Ok(GenericLsag {
+        ring,
+        message,
+        c0,
+        responses,
+        key_image,
+        linkability_flag: if header.flags & FLAG_LINKABILITY_FLAG != 0 {
+            Some(linkability_flag)
+        } else {
+            None
+        },
+        config: header.config.clone(),
+        evm_witnesses,
+    })
+}
+
+fn read_sag<C: RingCurve>(header: &Header, reader: &mut Reader) -> Result<GenericSag<C>, String> {
+    let (ring, c0, responses) = read_ring_and_scalars::<C>(reader)?;
+    let message = reader.read_string()?;
+
+    Ok(GenericSag {
+        ring,
+        message,
+        c0,
+        responses,
+        config: header.config.clone(),
+    })
+}
+
+/// Reads the binary format with bound checks
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    fn read(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "Unexpected end of the binary signature".to_string())?;
+
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let bytes = self.read(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.read(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn read_point<C: RingCurve>(&mut self) -> Result<C::Point, String> {
+        C::deserialize_point(&hex::encode(self.read(point_size::<C>())?))
+    }
+
+    pub(crate) fn read_scalar<C: RingCurve>(&mut self) -> Result<C::Scalar, String> {
+        C::scalar_from_hex(&hex::encode(self.read(SCALAR_SIZE)?))
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        let length = self.read_u32()? as usize;
+
+        String::from_utf8(self.read(length)?.to_vec())
+            .map_err(|_| "Invalid UTF-8 string".to_string())
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), String> {
+        if self.position != self.bytes.len() {
+            return Err("Trailing bytes after the binary signature".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_generic_lsag;
+    use crate::lsag_verifier::{verify_lsag, RistrettoLsag};
+    use crate::ring::GenericRing;
+    use crate::sag_verifier::verify_sag;
+    use crate::utils::curve::Ristretto255;
+    use serde_json::Value;
+    use std::fs;
+
+    fn load_ts_signature(path: &str) -> String {
+        let data = fs::read_to_string(path).expect("Unable to read json file");
+        let json_data: Value = serde_json::from_str(&data).expect("JSON was not well-formatted");
+
+        json_data["valid"].to_string()
+    }
+
+    #[test]
+    fn test_lsag_binary_round_trip() {
+        let json = load_ts_signature("../lsag-ts/test/data/jsonSignatures.json");
+        let mut signature = Lsag::from_json(&json).unwrap();
+        signature.evm_witnesses = Some(vec!["witness".to_string()]);
+        signature.config = Some(SignatureConfig {
+            evm_compatibility: None,
+            hash: Some(HashFunction::Keccak256),
+        });
+
+        let bytes = signature.to_bytes().unwrap();
+        // header + ring size + 4 points + c0 + 4 responses + key image + flag + message + witnesses
+        let expected_size = 5 + 2 + 4 * 33 + 32 + 4 * 32 + 33 + 4 + 16 + 4 + 7 + 2 + 4 + 7;
+        assert_eq!(bytes.len(), expected_size);
+
+        let decoded = Lsag::from_bytes(&bytes).expect("failed to decode the signature");
+        assert_eq!(decoded, signature);
+        assert!(verify_lsag(decoded));
+        assert!(Sag::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_sag_binary_round_trip() {
+        let json = load_ts_signature("../sag-ts/test/data/jsonSignatures.json");
+        let signature = Sag::from_json(&json).unwrap();
+
+        let decoded = Sag::from_bytes(&signature.to_bytes().unwrap()).expect("failed to decode");
+        assert_eq!(decoded, signature);
+        assert!(decoded.is_evm_compatible());
+        assert!(verify_sag(decoded));
+    }
+
+    #[test]
+    fn test_generic_binary_round_trip() {
+        let ring = GenericRing::<Ristretto255>::new(
+            [2u64, 3, 4]
+                .iter()
+                .map(|k| Ristretto255::mul_generator(&curve25519_dalek::Scalar::from(*k)))
+                .collect(),
+        )
+        .unwrap();
+        let signature = sign_generic_lsag::<Ristretto255>(
+            &ring,
+            curve25519_dalek::Scalar::from(3u64),
+            "message".to_string(),
+            None,
+        )
+        .unwrap();
+
+        let bytes = signature.to_bytes().unwrap();
+        // header + ring size + 3 points + c0 + 3 responses + key image + flag + message
+        assert_eq!(bytes.len(), 5 + 2 + 3 * 32 + 32 + 3 * 32 + 32 + 4 + 4 + 7);
+        let decoded = RistrettoLsag::from_bytes(&bytes).expect("failed to decode");
+        assert_eq!(decoded, signature);
+        assert!(verify_lsag(decoded));
+
+        // the curve of the signature is checked
+        assert!(Lsag::from_bytes(&bytes).is_err());
+        assert!(RingSignature::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_json_conversion() {
+        let signature = Lsag::from_json(&load_ts_signature(
+            "../lsag-ts/test/data/jsonSignatures.json",
+        ))
+        .unwrap();
+        let json = signature.to_json();
+
+        let bytes = json_to_bytes(&json).unwrap();
+        assert_eq!(bytes[1], Scheme::Lsag as u8);
+        assert_eq!(bytes_to_json(&bytes).unwrap(), json);
+        assert!(bytes.len() * 2 < signature.to_base64().len());
+
+        let sag_json = Sag::from_json(&load_ts_signature(
+            "../sag-ts/test/data/jsonSignatures.json",
+        ))
+        .unwrap()
+        .to_json();
+        let sag_bytes = json_to_bytes(&sag_json).unwrap();
+        assert_eq!(sag_bytes[1], Scheme::Sag as u8);
+        assert_eq!(bytes_to_json(&sag_bytes).unwrap(), sag_json);
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let json = load_ts_signature("../lsag-ts/test/data/jsonSignatures.json");
+        let bytes = Lsag::from_json(&json).unwrap().to_bytes().unwrap();
+
+        // truncated signature
+        assert!(RingSignature::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        // trailing bytes
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(RingSignature::from_bytes(&trailing).is_err());
+        // unknown version
+        let mut version = bytes.clone();
+        version[0] = 2;
+        assert!(RingSignature::from_bytes(&version).is_err());
+        // unknown curve
+        let mut curve = bytes;
+        curve[2] = 42;
+        assert!(RingSignature::from_bytes(&curve).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_responses() {
+        let json = load_ts_signature("../lsag-ts/test/data/jsonSignatures.json");
+        let mut lsag = Lsag::from_json(&json).unwrap();
+        lsag.responses.pop();
+        assert!(lsag.to_bytes().is_err());
+
+        let json = load_ts_signature("../sag-ts/test/data/jsonSignatures.json");
+        let mut sag = Sag::from_json(&json).unwrap();
+        sag.responses.push(sag.responses[0]);
+        assert!(sag.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_length_overflow() {
+        let json = load_ts_signature("../lsag-ts/test/data/jsonSignatures.json");
+        let signature = Lsag::from_json(&json).unwrap();
+
+        // the ring size does not fit in 2 bytes
+        let mut large_ring = signature.clone();
+        large_ring.ring = vec![signature.ring[0]; u16::MAX as usize + 1];
+        large_ring.responses = vec![signature.responses[0]; u16::MAX as usize + 1];
+        assert!(large_ring.to_bytes().is_err());
+
+        let mut many_witnesses = signature;
+        many_witnesses.evm_witnesses = Some(vec![String::new(); u16::MAX as usize + 1]);
+        assert!(many_witnesses.to_bytes().is_err());
+    }
+}
@@ -0,0 +1,416 @@
+//! Extracts secp256k1 public keys from Bitcoin data, so that Bitcoin holders can form rings:
+//! - P2WPKH witnesses of raw segwit transactions
+//! - PSBT inputs (BIP174 and BIP370): partial signatures, BIP32 derivations, Taproot keys
+//!   and P2TR outputs being spent
+//!
+//! x-only (BIP340) keys are lifted to the point with an even y, see `lift_x_only`. A member
+//! whose private key has an odd y public key signs with `sign_bitcoin_lsag`, which negates the
+//! key as BIP340 does.
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::Lsag;
+use crate::utils::serialize_point::{even_y_private_key, lift_x_only, parse_public_key_bytes};
+use base64::engine::general_purpose;
+use base64::Engine;
+use k256::{AffinePoint, Scalar};
+
+/// The magic bytes starting a PSBT
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+/// PSBT_GLOBAL_UNSIGNED_TX
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+/// PSBT_GLOBAL_INPUT_COUNT (PSBT v2)
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+/// PSBT_IN_WITNESS_UTXO
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+/// PSBT_IN_PARTIAL_SIG
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+/// PSBT_IN_BIP32_DERIVATION
+const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+/// PSBT_IN_TAP_BIP32_DERIVATION
+const PSBT_IN_TAP_BIP32_DERIVATION: u8 = 0x16;
+/// PSBT_IN_TAP_INTERNAL_KEY
+const PSBT_IN_TAP_INTERNAL_KEY: u8 = 0x17;
+
+/// Returns the public keys revealed by the P2WPKH witnesses of a raw segwit transaction,
+/// in input order. Inputs without a P2WPKH witness are skipped.
+pub fn witness_public_keys(raw_transaction: &[u8]) -> Result<Vec<AffinePoint>, String> {
+    let transaction = parse_transaction(raw_transaction)?;
+
+    let mut public_keys = Vec::new();
+    for witness in transaction.witnesses {
+        // P2WPKH witness: <signature> <compressed public key>
+        if let [_, public_key] = witness.as_slice() {
+            if public_key.len() == 33 {
+                public_keys.push(parse_public_key_bytes(public_key)?);
+            }
+        }
+    }
+
+    Ok(public_keys)
+}
+
+/// Returns the public keys found in the inputs of a PSBT, without duplicates:
+/// partial signature keys, BIP32 derivation keys, Taproot internal and derivation keys,
+/// and the output keys of the P2TR outputs being spent
+pub fn psbt_public_keys(psbt: &[u8]) -> Result<Vec<AffinePoint>, String> {
+    let mut reader = Reader::new(psbt);
+    if reader.read(PSBT_MAGIC.len())? != PSBT_MAGIC {
+        return Err("Invalid PSBT magic bytes".to_string());
+    }
+
+    let mut input_count = None;
+    for (key, value) in reader.read_map()? {
+        match key[0] {
+            PSBT_GLOBAL_UNSIGNED_TX => {
+                input_count = Some(parse_transaction(value)?.input_count);
+            }
+            PSBT_GLOBAL_INPUT_COUNT => {
+                input_count = Some(Reader::new(value).read_compact_size()?);
+            }
+            _ => {}
+        }
+    }
+    let input_count = input_count.ok_or_else(|| "Missing PSBT input count".to_string())?;
+
+    let mut public_keys: Vec<AffinePoint> = Vec::new();
+    for _ in 0..input_count {
+        for (key, value) in reader.read_map()? {
+            let public_key = match key[0] {
+                PSBT_IN_PARTIAL_SIG | PSBT_IN_BIP32_DERIVATION => {
+                    Some(parse_public_key_bytes(&key[1..])?)
+                }
+                PSBT_IN_TAP_BIP32_DERIVATION => Some(lift_x_only(&key[1..])?),
+                PSBT_IN_TAP_INTERNAL_KEY => Some(lift_x_only(value)?),
+                // witness utxo: amount (8) | script. A P2TR script is OP_1 <32-byte output key>
+                PSBT_IN_WITNESS_UTXO => {
+                    let mut utxo = Reader::new(value);
+                    utxo.read(8)?;
+                    let script = utxo.read_var_bytes()?;
+                    match script {
+                        [0x51, 0x20, output_key @ ..] if output_key.len() == 32 => {
+                            Some(lift_x_only(output_key)?)
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            if let Some(public_key) = public_key {
+                if !public_keys.contains(&public_key) {
+                    public_keys.push(public_key);
+                }
+            }
+        }
+    }
+
+    Ok(public_keys)
+}
+
+/// Returns the public keys found in the inputs of a base64 encoded PSBT
+pub fn psbt_public_keys_from_base64(b64_psbt: &str) -> Result<Vec<AffinePoint>, String> {
+    let psbt = general_purpose::STANDARD
+        .decode(b64_psbt.trim())
+        .map_err(|e| format!("Invalid base64 string: {}", e))?;
+
+    psbt_public_keys(&psbt)
+}
+
+/// Signs a message with a LSAG on behalf of a ring of Bitcoin public keys.
+///
+/// If the signer public key is not in the ring, the private key is negated as BIP340 does, for
+/// rings of lifted x-only keys. Returns an error if neither public key is a member of the ring.
+pub fn sign_bitcoin_lsag(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Lsag, String> {
+    let signer_private_key = [signer_private_key, even_y_private_key(signer_private_key)]
+        .into_iter()
+        .find(|private_key| ring.contains(&(AffinePoint::GENERATOR * private_key).to_affine()))
+        .ok_or_else(|| "The signer public key is not a member of the ring".to_string())?;
+
+    sign_lsag(ring, signer_private_key, message, linkability_flag)
+}
+
+/// The parts of a transaction needed to extract public keys
+struct Transaction<'a> {
+    input_count: usize,
+    /// The witness stack of every input, empty for non-segwit transactions
+    witnesses: Vec<Vec<&'a [u8]>>,
+}
+
+fn parse_transaction(raw_transaction: &[u8]) -> Result<Transaction<'_>, String> {
+    let mut reader = Reader::new(raw_transaction);
+    reader.read(4)?; // version
+
+    // segwit transactions have a 0x00 marker and a 0x01 flag
+    let is_segwit = reader.peek(2) == Some(&[0x00, 0x01]);
+    if is_segwit {
+        reader.read(2)?;
+    }
+
+    let input_count = reader.read_compact_size()?;
+    for _ in 0..input_count {
+        reader.read(36)?; // previous output
+        reader.read_var_bytes()?; // script sig
+        reader.read(4)?; // sequence
+    }
+
+    let output_count = reader.read_compact_size()?;
+    for _ in 0..output_count {
+        reader.read(8)?; // amount
+        reader.read_var_bytes()?; // script pubkey
+    }
+
+    let mut witnesses = Vec::new();
+    if is_segwit {
+        for _ in 0..input_count {
+            let item_count = reader.read_compact_size()?;
+            let mut witness = Vec::new();
+            for _ in 0..item_count {
+                witness.push(reader.read_var_bytes()?);
+            }
+            witnesses.push(witness);
+        }
+    }
+
+    reader.read(4)?; // lock time
+    if !reader.is_empty() {
+        return Err("Unexpected trailing bytes in transaction".to_string());
+    }
+
+    Ok(Transaction {
+        input_count,
+        witnesses,
+    })
+}
+
+/// The key-value pairs of a PSBT map
+type PsbtMap<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// A bounds-checked reader over Bitcoin serialized data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    fn peek(&self, length: usize) -> Option<&'a [u8]> {
+        self.bytes
+            .get(self.position..self.position.checked_add(length)?)
+    }
+
+    fn read(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .peek(length)
+            .ok_or_else(|| "Unexpected end of data".to_string())?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn read_compact_size(&mut self) -> Result<usize, String> {
+        let size = match self.read(1)?[0] {
+            0xfd => u16::from_le_bytes(self.read(2)?.try_into().unwrap()) as u64,
+            0xfe => u32::from_le_bytes(self.read(4)?.try_into().unwrap()) as u64,
+            0xff => u64::from_le_bytes(self.read(8)?.try_into().unwrap()),
+            size => size as u64,
+        };
+
+        usize::try_from(size).map_err(|_| "Invalid compact size".to_string())
+    }
+
+    fn read_var_bytes(&mut self) -> Result<&'a [u8], String> {
+        let length = self.read_compact_size()?;
+        self.read(length)
+    }
+
+    /// Reads a PSBT map: key-value pairs terminated by a 0x00 byte
+    fn read_map(&mut self) -> Result<PsbtMap<'a>, String> {
+        let mut map = Vec::new();
+        loop {
+            let key = self.read_var_bytes()?;
+            if key.is_empty() {
+                return Ok(map);
+            }
+            map.push((key, self.read_var_bytes()?));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_verifier::verify_lsag;
+    use crate::ring::Ring;
+    use elliptic_curve::sec1::ToEncodedPoint;
+
+    fn get_point(private_key: u64) -> AffinePoint {
+        (AffinePoint::GENERATOR * Scalar::from(private_key)).to_affine()
+    }
+
+    fn compressed(point: &AffinePoint) -> Vec<u8> {
+        point.to_encoded_point(true).as_bytes().to_vec()
+    }
+
+    fn x_only(point: &AffinePoint) -> Vec<u8> {
+        compressed(point)[1..].to_vec()
+    }
+
+    fn var_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![bytes.len() as u8];
+        encoded.extend_from_slice(bytes);
+        encoded
+    }
+
+    /// Builds a transaction with one output and the given witnesses (one input per witness)
+    fn build_transaction(witnesses: Option<&[Vec<Vec<u8>>]>, input_count: usize) -> Vec<u8> {
+        let mut transaction = vec![2, 0, 0, 0];
+        if witnesses.is_some() {
+            transaction.extend([0x00, 0x01]);
+        }
+        transaction.push(input_count as u8);
+        for i in 0..input_count {
+            transaction.extend([i as u8; 36]);
+            transaction.push(0); // empty script sig
+            transaction.extend([0xff; 4]);
+        }
+        transaction.push(1);
+        transaction.extend([0; 8]);
+        transaction.extend(var_bytes(&[0x00, 0x14]));
+        if let Some(witnesses) = witnesses {
+            for witness in witnesses {
+                transaction.push(witness.len() as u8);
+                for item in witness {
+                    transaction.extend(var_bytes(item));
+                }
+            }
+        }
+        transaction.extend([0; 4]);
+        transaction
+    }
+
+    #[test]
+    fn test_witness_public_keys() {
+        let (alice, bob) = (get_point(2), get_point(3));
+        let transaction = build_transaction(
+            Some(&[
+                vec![vec![0x30; 71], compressed(&alice)],
+                // a taproot key path spend does not reveal the key
+                vec![vec![0x01; 64]],
+                vec![vec![0x30; 72], compressed(&bob)],
+            ]),
+            3,
+        );
+
+        assert_eq!(witness_public_keys(&transaction), Ok(vec![alice, bob]));
+        assert_eq!(witness_public_keys(&build_transaction(None, 1)), Ok(vec![]));
+        assert!(witness_public_keys(&transaction[..transaction.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_psbt_public_keys() {
+        let (alice, bob, carol, dave) = (get_point(2), get_point(3), get_point(4), get_point(5));
+
+        let mut psbt = PSBT_MAGIC.to_vec();
+        // global map
+        psbt.extend(var_bytes(&[PSBT_GLOBAL_UNSIGNED_TX]));
+        psbt.extend(var_bytes(&build_transaction(None, 2)));
+        psbt.push(0);
+        // first input: a P2WPKH input with a partial signature and a BIP32 derivation
+        let mut key = vec![PSBT_IN_PARTIAL_SIG];
+        key.extend(compressed(&alice));
+        psbt.extend(var_bytes(&key));
+        psbt.extend(var_bytes(&[0x30; 71]));
+        let mut key = vec![PSBT_IN_BIP32_DERIVATION];
+        key.extend(compressed(&alice));
+        psbt.extend(var_bytes(&key));
+        psbt.extend(var_bytes(&[0; 8]));
+        psbt.push(0);
+        // second input: a P2TR input with its internal key, a derivation and the spent output
+        psbt.extend(var_bytes(&[PSBT_IN_TAP_INTERNAL_KEY]));
+        psbt.extend(var_bytes(&x_only(&bob)));
+        let mut key = vec![PSBT_IN_TAP_BIP32_DERIVATION];
+        key.extend(x_only(&carol));
+        psbt.extend(var_bytes(&key));
+        psbt.extend(var_bytes(&[0; 9]));
+        let mut witness_utxo = vec![0; 8];
+        let mut script = vec![0x51, 0x20];
+        script.extend(x_only(&dave));
+        witness_utxo.extend(var_bytes(&script));
+        psbt.extend(var_bytes(&[PSBT_IN_WITNESS_UTXO]));
+        psbt.extend(var_bytes(&witness_utxo));
+        psbt.push(0);
+        // output map
+        psbt.push(0);
+
+        let public_keys = psbt_public_keys(&psbt).expect("failed to parse the PSBT");
+        // x-only keys are lifted to the point with an even y
+        let lift = |point: &AffinePoint| lift_x_only(&x_only(point)).unwrap();
+        assert_eq!(
+            public_keys,
+            vec![alice, lift(&bob), lift(&carol), lift(&dave)]
+        );
+        assert_eq!(
+            psbt_public_keys_from_base64(&general_purpose::STANDARD.encode(&psbt)),
+            Ok(public_keys.clone())
+        );
+        assert!(psbt_public_keys(&psbt[..psbt.len() - 2]).is_err());
+
+        // PSBT v2: the input count is a global field
+        let mut psbt_v2 = PSBT_MAGIC.to_vec();
+        psbt_v2.extend(var_bytes(&[PSBT_GLOBAL_INPUT_COUNT]));
+        psbt_v2.extend(var_bytes(&[1]));
+        psbt_v2.push(0);
+        psbt_v2.extend(var_bytes(&[PSBT_IN_TAP_INTERNAL_KEY]));
+        psbt_v2.extend(var_bytes(&x_only(&bob)));
+        psbt_v2.extend([0, 0]);
+        assert_eq!(psbt_public_keys(&psbt_v2), Ok(vec![lift(&bob)]));
+        assert!(psbt_public_keys(b"not a psbt").is_err());
+
+        // the extracted keys form a ring usable by the LSAG signer and verifier
+        let ring = Ring::new(public_keys).unwrap();
+        let signature =
+            sign_bitcoin_lsag(&ring, Scalar::from(2u64), "message".to_string(), None).unwrap();
+        assert!(verify_lsag(signature));
+    }
+
+    #[test]
+    fn test_sign_with_odd_y_key() {
+        // a private key whose public key has an odd y
+        let private_key = (2u64..)
+            .find(|k| compressed(&get_point(*k))[0] == 0x03)
+            .unwrap();
+        let lifted = lift_x_only(&x_only(&get_point(private_key))).unwrap();
+        assert_ne!(lifted, get_point(private_key));
+
+        let ring = Ring::new(vec![get_point(42), get_point(43), lifted]).unwrap();
+        let signature = sign_bitcoin_lsag(
+            &ring,
+            Scalar::from(private_key),
+            "message".to_string(),
+            None,
+        )
+        .expect("failed to sign");
+        assert_eq!(signature.ring, ring.into_points());
+        assert!(verify_lsag(signature.clone()));
+
+        // the key is not inserted in a ring it is not a member of
+        assert!(sign_bitcoin_lsag(
+            &signature.ring,
+            Scalar::from(7u64),
+            "message".to_string(),
+            None
+        )
+        .is_err());
+    }
+}
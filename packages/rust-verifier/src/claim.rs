@@ -0,0 +1,428 @@
+//! Claimable ring signatures: the signer can later prove that they produced a signature.
+//!
+//! At signing time, a commitment to the signer public key and a random claim secret is appended
+//! to the message, so it is covered by the signature and the signature stays a regular `Lsag`
+//! or `Sag` that any verifier accepts:
+//!
+//! ```text
+//! <message>
+//! claim commitment: <keccak256("claim" || secret || public key), hex>
+//! ```
+//!
+//! The commitment does not reveal the signer. To come forward, the signer publishes a `Claim`:
+//! the public key, the claim secret opening the commitment, and a Schnorr proof of knowledge of
+//! the private key, bound to the signed message.
+//!
+//! The commitment alone does not bind the claim to the actual signer: a signer could commit to
+//! the public key of another ring member. For LSAG signatures, the claim also holds a DLEQ proof
+//! that the key image of the signature is the key image of the claim public key, so only the
+//! signer can claim it. SAG signatures have no key image, so a SAG claim only proves that the
+//! claimant knows the commitment opening and the private key of a ring member: a signer can let
+//! another member claim their signature.
+use crate::dleq::{prove_key_image, verify_key_image, DleqProof, StringifiedDleqProof};
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::{verify_lsag, Lsag};
+use crate::sag_signer::sign_sag;
+use crate::sag_verifier::{verify_sag, Sag};
+use crate::utils::curve::RingCurve;
+use crate::utils::signature_config::{HashFunction, SignatureConfig};
+use k256::{AffinePoint, Scalar, Secp256k1};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// The line appended to the message of a claimable signature, before the commitment
+pub const CLAIM_COMMITMENT_PREFIX: &str = "\nclaim commitment: ";
+
+const COMMITMENT_DOMAIN: &[u8] = b"claim";
+const PROOF_DOMAIN: &str = "claim_proof";
+const KEY_IMAGE_PROOF_DOMAIN: &str = "claim_key_image";
+
+/// The random secret opening the commitment of a claimable signature.
+/// It must be kept by the signer to claim the signature later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimSecret(pub [u8; 32]);
+
+impl ClaimSecret {
+    /// Generates a random claim secret
+    pub fn random() -> ClaimSecret {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        ClaimSecret(secret)
+    }
+
+    /// Parses a claim secret from a hex string
+    pub fn from_hex(hex_str: &str) -> Result<ClaimSecret, String> {
+        let bytes = hex::decode(hex_str).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+        Ok(ClaimSecret(bytes.try_into().map_err(|_| {
+            "A claim secret must be 32 bytes long".to_string()
+        })?))
+    }
+
+    /// Converts the claim secret to a hex string
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Computes the commitment to `public_key` embedded in the signed message
+    pub fn commitment(&self, public_key: &AffinePoint) -> String {
+        let mut hasher = Keccak256::new();
+        hasher.update(COMMITMENT_DOMAIN);
+        hasher.update(self.0);
+        hasher.update(Secp256k1::serialize_point(public_key).as_bytes());
+
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// A ring signature that can be claimed
+pub trait ClaimableSignature {
+    /// Returns the signed message, ending with the claim commitment
+    fn signed_message(&self) -> &str;
+
+    /// Returns the ring of the signature
+    fn ring_points(&self) -> &[AffinePoint];
+
+    /// Verifies the signature itself
+    fn verify_signature(&self) -> bool;
+
+    /// Returns the key image and the linkability flag of a linkable signature,
+    /// which bind a claim to the actual signer
+    fn key_image(&self) -> Option<(&AffinePoint, &Option<String>)> {
+        None
+    }
+}
+
+impl ClaimableSignature for Lsag {
+    fn signed_message(&self) -> &str {
+        &self.message
+    }
+
+    fn ring_points(&self) -> &[AffinePoint] {
+        &self.ring
+    }
+
+    fn verify_signature(&self) -> bool {
+        verify_lsag(self.clone())
+    }
+
+    fn key_image(&self) -> Option<(&AffinePoint, &Option<String>)> {
+        Some((&self.key_image, &self.linkability_flag))
+    }
+}
+
+impl ClaimableSignature for Sag {
+    fn signed_message(&self) -> &str {
+        &self.message
+    }
+
+    fn ring_points(&self) -> &[AffinePoint] {
+        &self.ring
+    }
+
+    fn verify_signature(&self) -> bool {
+        verify_sag(self.clone())
+    }
+}
+
+/// Returns the message of a claimable signature without its claim commitment,
+/// and the commitment
+pub fn split_claimable_message(message: &str) -> Option<(&str, &str)> {
+    let (original, commitment) = message.rsplit_once(CLAIM_COMMITMENT_PREFIX)?;
+
+    (commitment.len() == 64 && commitment.chars().all(|c| c.is_ascii_hexdigit()))
+        .then_some((original, commitment))
+}
+
+/// Signs a message using a claimable LSAG ring signature (see `sign_lsag`).
+/// Returns the signature and the claim secret to keep.
+pub fn sign_claimable_lsag(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<(Lsag, ClaimSecret), String> {
+    let (secret, message) = claimable_message(&signer_private_key, message);
+    let signature = sign_lsag(ring, signer_private_key, message, linkability_flag)?;
+
+    Ok((signature, secret))
+}
+
+/// Signs a message using a claimable SAG ring signature (see `sign_sag`).
+/// Returns the signature and the claim secret to keep.
+pub fn sign_claimable_sag(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    message: String,
+    config: Option<SignatureConfig>,
+) -> Result<(Sag, ClaimSecret), String> {
+    let (secret, message) = claimable_message(&signer_private_key, message);
+    let signature = sign_sag(ring, signer_private_key, message, config)?;
+
+    Ok((signature, secret))
+}
+
+fn claimable_message(signer_private_key: &Scalar, message: String) -> (ClaimSecret, String) {
+    let secret = ClaimSecret::random();
+    let commitment = secret.commitment(&Secp256k1::mul_generator(signer_private_key));
+
+    (secret, message + CLAIM_COMMITMENT_PREFIX + &commitment)
+}
+
+/// The JSON representation of a claim
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StringifiedClaim {
+    pub publicKey: String,
+    pub secret: String,
+    pub c: String,
+    pub response: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyImageProof: Option<StringifiedDleqProof>,
+}
+
+/// A proof that the holder of `public_key` produced a claimable signature
+#[derive(Clone, Debug, PartialEq)]
+pub struct Claim {
+    pub public_key: AffinePoint,
+    pub secret: ClaimSecret,
+    /// The challenge of the Schnorr proof of knowledge of the private key
+    pub c: Scalar,
+    pub response: Scalar,
+    /// The proof that the key image of a LSAG signature belongs to `public_key`
+    /// (`None` for SAG signatures)
+    pub key_image_proof: Option<DleqProof>,
+}
+
+impl Claim {
+    /// Parses a claim from its JSON string
+    pub fn from_json(json: &str) -> Result<Claim, String> {
+        let stringified: StringifiedClaim =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        Ok(Claim {
+            public_key: Secp256k1::deserialize_point(&stringified.publicKey)?,
+            secret: ClaimSecret::from_hex(&stringified.secret)?,
+            c: Secp256k1::scalar_from_hex(&stringified.c)?,
+            response: Secp256k1::scalar_from_hex(&stringified.response)?,
+            key_image_proof: stringified
+                .keyImageProof
+                .map(DleqProof::try_from)
+                .transpose()?,
+        })
+    }
+
+    /// Converts the claim to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedClaim {
+            publicKey: Secp256k1::serialize_point(&self.public_key),
+            secret: self.secret.to_hex(),
+            c: Secp256k1::scalar_to_hex(&self.c),
+            response: Secp256k1::scalar_to_hex(&self.response),
+            keyImageProof: self
+                .key_image_proof
+                .as_ref()
+                .map(StringifiedDleqProof::from),
+        })
+        .unwrap()
+    }
+}
+
+/// Creates the claim of a claimable signature, with the claim secret returned at signing time
+/// and the signer private key
+pub fn create_claim<S: ClaimableSignature>(
+    signature: &S,
+    secret: &ClaimSecret,
+    signer_private_key: &Scalar,
+) -> Result<Claim, String> {
+    let (_, commitment) = split_claimable_message(signature.signed_message())
+        .ok_or_else(|| "The signature is not claimable".to_string())?;
+
+    let public_key = Secp256k1::mul_generator(signer_private_key);
+    if !signature.ring_points().contains(&public_key) {
+        return Err("The signer is not a member of the ring".to_string());
+    }
+    if secret.commitment(&public_key) != commitment {
+        return Err("The claim secret does not match the commitment".to_string());
+    }
+
+    let key_image_proof = match signature.key_image() {
+        Some((key_image, linkability_flag)) => {
+            let (signer_key_image, proof) = prove_key_image(
+                signer_private_key,
+                linkability_flag,
+                &key_image_proof_context(signature.signed_message()),
+                HashFunction::Keccak256,
+            );
+            if signer_key_image != *key_image {
+                return Err("The signature was not signed with this private key".to_string());
+            }
+            Some(proof)
+        }
+        None => None,
+    };
+
+    let nonce = Secp256k1::random_scalar();
+    let c = compute_proof_challenge(
+        signature.signed_message(),
+        &public_key,
+        &Secp256k1::mul_generator(&nonce),
+    );
+
+    Ok(Claim {
+        public_key,
+        secret: secret.clone(),
+        c,
+        response: nonce - c * signer_private_key,
+        key_image_proof,
+    })
+}
+
+/// Verifies a claim against the signature it claims.
+/// Returns `true` if the signature is valid and was produced by the holder of the claim
+/// public key, `false` otherwise. For SAG signatures, see the limitation in the module docs.
+pub fn verify_claim<S: ClaimableSignature>(signature: &S, claim: &Claim) -> bool {
+    let Some((_, commitment)) = split_claimable_message(signature.signed_message()) else {
+        return false;
+    };
+    if !signature.ring_points().contains(&claim.public_key)
+        || claim.secret.commitment(&claim.public_key) != commitment
+    {
+        return false;
+    }
+
+    let is_signer = match (signature.key_image(), &claim.key_image_proof) {
+        (Some((key_image, linkability_flag)), Some(proof)) => verify_key_image(
+            proof,
+            &claim.public_key,
+            key_image,
+            linkability_flag,
+            &key_image_proof_context(signature.signed_message()),
+        ),
+        (None, None) => true,
+        _ => false,
+    };
+    if !is_signer {
+        return false;
+    }
+
+    // R = s*G + c*K
+    let nonce_point = Secp256k1::lincomb(
+        &Secp256k1::generator(),
+        &claim.response,
+        &claim.public_key,
+        &claim.c,
+    );
+    if compute_proof_challenge(signature.signed_message(), &claim.public_key, &nonce_point)
+        != claim.c
+    {
+        return false;
+    }
+
+    signature.verify_signature()
+}
+
+/// Returns the context of the key image proof, bound to the signed message
+fn key_image_proof_context(signed_message: &str) -> String {
+    KEY_IMAGE_PROOF_DOMAIN.to_string() + signed_message
+}
+
+/// Computes the challenge of the Schnorr proof, bound to the signed message
+fn compute_proof_challenge(
+    signed_message: &str,
+    public_key: &AffinePoint,
+    nonce_point: &AffinePoint,
+) -> Scalar {
+    let hash_content = PROOF_DOMAIN.to_string()
+        + &Secp256k1::serialize_point(public_key)
+        + &Secp256k1::serialize_point(nonce_point)
+        + signed_message;
+
+    Secp256k1::scalar_from_hash(&Keccak256::digest(hash_content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_claim_lsag() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let signer_private_key = Scalar::from(3u64);
+
+        let (signature, secret) = sign_claimable_lsag(
+            &ring,
+            signer_private_key,
+            "leaked document".to_string(),
+            None,
+        )
+        .expect("failed to sign");
+        assert_eq!(
+            split_claimable_message(&signature.message).unwrap().0,
+            "leaked document"
+        );
+        assert!(verify_lsag(signature.clone()));
+
+        let claim = create_claim(&signature, &secret, &signer_private_key).unwrap();
+        assert_eq!(
+            claim.public_key,
+            Secp256k1::mul_generator(&signer_private_key)
+        );
+        assert!(verify_claim(&signature, &claim));
+        assert_eq!(Claim::from_json(&claim.to_json()), Ok(claim.clone()));
+
+        // another member cannot claim the signature, even with the claim secret
+        assert!(create_claim(&signature, &secret, &Scalar::from(2u64)).is_err());
+        let mut stolen = claim.clone();
+        stolen.public_key = Secp256k1::mul_generator(&Scalar::from(2u64));
+        assert!(!verify_claim(&signature, &stolen));
+
+        // the claim is bound to the signature
+        let (other, other_secret) =
+            sign_claimable_lsag(&ring, signer_private_key, "other".to_string(), None).unwrap();
+        assert!(!verify_claim(&other, &claim));
+        assert!(create_claim(&other, &secret, &signer_private_key).is_err());
+        assert!(verify_claim(
+            &other,
+            &create_claim(&other, &other_secret, &signer_private_key).unwrap()
+        ));
+
+        // the claim is bound to the signer: a member cannot claim a signature that another
+        // member committed to their public key
+        let commitment = secret.commitment(&claim.public_key);
+        let impersonated = sign_lsag(
+            &ring,
+            Scalar::from(2u64),
+            "leaked document".to_string() + CLAIM_COMMITMENT_PREFIX + &commitment,
+            None,
+        )
+        .unwrap();
+        assert!(create_claim(&impersonated, &secret, &signer_private_key).is_err());
+        let mut without_proof = claim;
+        without_proof.key_image_proof = None;
+        assert!(!verify_claim(&signature, &without_proof));
+    }
+
+    #[test]
+    fn test_claim_sag() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let signer_private_key = Scalar::from(42u64);
+
+        let (signature, secret) =
+            sign_claimable_sag(&ring, signer_private_key, "message".to_string(), None)
+                .expect("failed to sign");
+        let claim = create_claim(&signature, &secret, &signer_private_key).unwrap();
+        assert!(verify_claim(&signature, &claim));
+        assert_eq!(claim.key_image_proof, None);
+
+        let mut forged = claim;
+        forged.response += Scalar::ONE;
+        assert!(!verify_claim(&signature, &forged));
+
+        let unclaimable = sign_sag(&ring, signer_private_key, "message".to_string(), None).unwrap();
+        assert!(create_claim(&unclaimable, &secret, &signer_private_key).is_err());
+        assert!(!verify_claim(&unclaimable, &forged));
+    }
+}
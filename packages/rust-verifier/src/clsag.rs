@@ -0,0 +1,536 @@
+//! CLSAG: concise linkable ring signatures, as used by Monero.
+//!
+//! Each ring member can have a second key, a commitment key (e.g. the difference between an
+//! amount commitment and a pseudo output commitment). The two keys are aggregated with hashed
+//! coefficients, so a signature has a single response per ring member where an MLSAG over two
+//! keys needs two of them. The signer also proves knowledge of the commitment private key and
+//! publishes its auxiliary key image, which is not used for linking.
+//!
+//! Key images are computed as for LSAG signatures (`x * H(K, flag)`), so a CLSAG and a LSAG
+//! signed by the same key with the same linkability flag are linked.
+//!
+//! Only secp256k1 is supported, and there is no TS implementation yet.
+use crate::lsag_signer::insert_signer;
+use crate::lsag_verifier::compute_mapped_point;
+use crate::utils::base64_json::decode_base64_json;
+use crate::utils::curve::{Curve, RingCurve};
+use crate::utils::hex_to_decimal::hex_to_decimal;
+use crate::utils::keccak256::keccak_256;
+use crate::utils::linkability_flag::parse_linkability_flag;
+use base64::engine::general_purpose;
+use base64::Engine;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+const AGGREGATION_DOMAIN_0: &str = "CLSAG_agg_0";
+const AGGREGATION_DOMAIN_1: &str = "CLSAG_agg_1";
+const ROUND_DOMAIN: &str = "CLSAG_round";
+
+/// The JSON representation of a CLSAG signature, in the format of the LSAG signatures
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StringifiedClsag {
+    pub message: String,
+    pub ring: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commitments: Option<Vec<String>>,
+    pub c: String,
+    pub responses: Vec<String>,
+    pub curve: String,
+    pub keyImage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxiliaryKeyImage: Option<String>,
+    pub linkabilityFlag: String,
+}
+
+/// A CLSAG signature on secp256k1
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clsag {
+    pub ring: Vec<AffinePoint>,
+    /// The commitment key of each ring member, if the members have one
+    pub commitments: Option<Vec<AffinePoint>>,
+    pub message: String,
+    pub c0: Scalar,
+    pub responses: Vec<Scalar>,
+    pub key_image: AffinePoint,
+    /// The key image of the signer commitment key, set if the ring members have commitments
+    pub auxiliary_key_image: Option<AffinePoint>,
+    pub linkability_flag: Option<String>,
+}
+
+impl Clsag {
+    /// Parses a CLSAG signature from its JSON string
+    pub fn from_json(json: &str) -> Result<Clsag, String> {
+        let stringified: StringifiedClsag =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        Clsag::try_from(stringified)
+    }
+
+    /// Converts the signature to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedClsag::from(self)).unwrap()
+    }
+
+    /// Parses a CLSAG signature from the base64 encoding of its JSON string
+    pub fn from_base64(b64_signature: &str) -> Result<Clsag, String> {
+        Clsag::from_json(&decode_base64_json(b64_signature)?)
+    }
+
+    /// Converts the signature to the base64 encoding of its JSON string
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.to_json())
+    }
+}
+
+impl TryFrom<StringifiedClsag> for Clsag {
+    type Error = String;
+
+    fn try_from(json: StringifiedClsag) -> Result<Clsag, String> {
+        if json.ring.is_empty() {
+            return Err("Ring cannot be empty".to_string());
+        }
+        if json.ring.len() != json.responses.len() {
+            return Err("Ring and responses must have the same length".to_string());
+        }
+        if json.commitments.is_some() != json.auxiliaryKeyImage.is_some() {
+            return Err("Commitments and auxiliary key image must be set together".to_string());
+        }
+
+        let curve = Curve::from_json_string(&json.curve)?;
+        if curve != Curve::Secp256k1 {
+            return Err(format!("Unsupported curve: {}", curve.name()));
+        }
+
+        let ring = parse_points(&json.ring)?;
+        let commitments = json.commitments.as_deref().map(parse_points).transpose()?;
+        if commitments
+            .as_ref()
+            .is_some_and(|commitments| commitments.len() != ring.len())
+        {
+            return Err("Ring and commitments must have the same length".to_string());
+        }
+
+        Ok(Clsag {
+            ring,
+            commitments,
+            message: json.message,
+            c0: Secp256k1::scalar_from_hex(&json.c)?,
+            responses: json
+                .responses
+                .iter()
+                .map(|response| Secp256k1::scalar_from_hex(response))
+                .collect::<Result<Vec<Scalar>, String>>()?,
+            key_image: Secp256k1::deserialize_point(&json.keyImage)?,
+            auxiliary_key_image: json
+                .auxiliaryKeyImage
+                .as_deref()
+                .map(Secp256k1::deserialize_point)
+                .transpose()?,
+            // `toJsonString()` writes a missing flag as an empty string
+            linkability_flag: parse_linkability_flag(json.linkabilityFlag),
+        })
+    }
+}
+
+impl From<&Clsag> for StringifiedClsag {
+    fn from(signature: &Clsag) -> StringifiedClsag {
+        StringifiedClsag {
+            message: signature.message.clone(),
+            ring: signature
+                .ring
+                .iter()
+                .map(Secp256k1::serialize_point)
+                .collect(),
+            commitments: signature
+                .commitments
+                .as_ref()
+                .map(|commitments| commitments.iter().map(Secp256k1::serialize_point).collect()),
+            c: Secp256k1::scalar_to_hex(&signature.c0),
+            responses: signature
+                .responses
+                .iter()
+                .map(Secp256k1::scalar_to_hex)
+                .collect(),
+            curve: Curve::Secp256k1.to_json_string(),
+            keyImage: Secp256k1::serialize_point(&signature.key_image),
+            auxiliaryKeyImage: signature
+                .auxiliary_key_image
+                .as_ref()
+                .map(Secp256k1::serialize_point),
+            linkabilityFlag: signature.linkability_flag.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// The values shared by every ring iteration
+struct Transcript {
+    serialized_members: String,
+    message: String,
+    mu_p: Scalar,
+    mu_c: Scalar,
+    aggregated_image: AffinePoint,
+}
+
+impl Transcript {
+    fn new(
+        ring: &[AffinePoint],
+        commitments: Option<&[AffinePoint]>,
+        message: &str,
+        key_image: &AffinePoint,
+        auxiliary_key_image: Option<&AffinePoint>,
+    ) -> Transcript {
+        let mut serialized_members: String = ring.iter().map(Secp256k1::serialize_point).collect();
+        if let Some(commitments) = commitments {
+            serialized_members += &commitments
+                .iter()
+                .map(Secp256k1::serialize_point)
+                .collect::<String>();
+        }
+        let serialized_images = Secp256k1::serialize_point(key_image)
+            + &auxiliary_key_image
+                .map(Secp256k1::serialize_point)
+                .unwrap_or_default();
+
+        let mu_p = hash_to_scalar(
+            AGGREGATION_DOMAIN_0.to_string() + &serialized_members + &serialized_images,
+        );
+        let (mu_c, aggregated_image) = match auxiliary_key_image {
+            Some(auxiliary_key_image) => {
+                let mu_c = hash_to_scalar(
+                    AGGREGATION_DOMAIN_1.to_string() + &serialized_members + &serialized_images,
+                );
+                let aggregated_image =
+                    Secp256k1::lincomb(key_image, &mu_p, auxiliary_key_image, &mu_c);
+                (mu_c, aggregated_image)
+            }
+            None => (Scalar::ZERO, Secp256k1::mul(key_image, &mu_p)),
+        };
+
+        Transcript {
+            serialized_members,
+            message: hex_to_decimal(&keccak_256(&[message.to_string()])).unwrap(),
+            mu_p,
+            mu_c,
+            aggregated_image,
+        }
+    }
+
+    /// Aggregates the keys of a ring member: `mu_P * K + mu_C * C`
+    fn aggregated_key(
+        &self,
+        public_key: &AffinePoint,
+        commitment: Option<&AffinePoint>,
+    ) -> AffinePoint {
+        match commitment {
+            Some(commitment) => Secp256k1::lincomb(public_key, &self.mu_p, commitment, &self.mu_c),
+            None => Secp256k1::mul(public_key, &self.mu_p),
+        }
+    }
+
+    /// Computes the next 'cee' value from `r*G + c*W` and `r*H(K, flag) + c*W~`
+    /// (or `alpha*G` and `alpha*H(K, flag)` for the signer)
+    fn compute_c(&self, point: &AffinePoint, mapped_point: &AffinePoint) -> Scalar {
+        hash_to_scalar(
+            ROUND_DOMAIN.to_string()
+                + &self.serialized_members
+                + &self.message
+                + &Secp256k1::serialize_point(point)
+                + &Secp256k1::serialize_point(mapped_point),
+        )
+    }
+}
+
+/// Signs a message with a CLSAG ring signature whose ring members only have a signing key.
+/// The signer public key is inserted in the ring, which is sorted as for `sign_lsag`.
+pub fn sign_clsag(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Clsag, String> {
+    if bool::from(signer_private_key.is_zero()) {
+        return Err("Signer private key cannot be 0".to_string());
+    }
+    let (ring, signer_index) = insert_signer(ring, &signer_private_key)?;
+
+    sign(
+        ring.into_points(),
+        None,
+        signer_index,
+        signer_private_key,
+        None,
+        message,
+        linkability_flag,
+    )
+}
+
+/// Signs a message with a CLSAG ring signature whose ring members have a signing key and a
+/// commitment key. The ring is used as given, and must contain the signer keys
+/// (`signer_private_key * G` with the commitment key `commitment_private_key * G`).
+pub fn sign_clsag_with_commitments(
+    ring: &[AffinePoint],
+    commitments: &[AffinePoint],
+    signer_private_key: Scalar,
+    commitment_private_key: Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Clsag, String> {
+    if bool::from(signer_private_key.is_zero()) {
+        return Err("Signer private key cannot be 0".to_string());
+    }
+    if ring.len() != commitments.len() {
+        return Err("Ring and commitments must have the same length".to_string());
+    }
+
+    let signer_public_key = Secp256k1::mul_generator(&signer_private_key);
+    let signer_index = ring
+        .iter()
+        .position(|point| *point == signer_public_key)
+        .ok_or_else(|| "The signer public key is not in the ring".to_string())?;
+    if commitments[signer_index] != Secp256k1::mul_generator(&commitment_private_key) {
+        return Err("The commitment private key does not match the signer commitment".to_string());
+    }
+
+    sign(
+        ring.to_vec(),
+        Some(commitments.to_vec()),
+        signer_index,
+        signer_private_key,
+        Some(commitment_private_key),
+        message,
+        linkability_flag,
+    )
+}
+
+fn sign(
+    ring: Vec<AffinePoint>,
+    commitments: Option<Vec<AffinePoint>>,
+    signer_index: usize,
+    signer_private_key: Scalar,
+    commitment_private_key: Option<Scalar>,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Clsag, String> {
+    let mapped = compute_mapped_point::<Secp256k1>(&ring[signer_index], &linkability_flag);
+    let key_image = Secp256k1::mul(&mapped, &signer_private_key);
+    let auxiliary_key_image = commitment_private_key.map(|z| Secp256k1::mul(&mapped, &z));
+
+    let transcript = Transcript::new(
+        &ring,
+        commitments.as_deref(),
+        &message,
+        &key_image,
+        auxiliary_key_image.as_ref(),
+    );
+
+    let alpha = Secp256k1::random_scalar();
+
+    // generate random responses for every public key in the ring
+    let mut responses: Vec<Scalar> = ring.iter().map(|_| Secp256k1::random_scalar()).collect();
+
+    // contains all the cees from 0 to ring.len() - 1 (0, 1, ..., pi, ..., ring.len() - 1)
+    let mut cees = vec![Scalar::ZERO; ring.len()];
+
+    // compute cpi+1
+    cees[(signer_index + 1) % ring.len()] = transcript.compute_c(
+        &Secp256k1::mul_generator(&alpha),
+        &Secp256k1::mul(&mapped, &alpha),
+    );
+
+    // compute the c values from pi+2 to pi (included)
+    for i in signer_index + 2..signer_index + ring.len() + 1 {
+        let index = i % ring.len();
+        let previous_index = (index + ring.len() - 1) % ring.len();
+
+        cees[index] = compute_next_c(
+            &transcript,
+            &ring,
+            commitments.as_deref(),
+            previous_index,
+            &responses[previous_index],
+            &cees[previous_index],
+            &linkability_flag,
+        );
+    }
+
+    // compute the signer response with the aggregated private key
+    let aggregated_private_key = transcript.mu_p * signer_private_key
+        + transcript.mu_c * commitment_private_key.unwrap_or(Scalar::ZERO);
+    responses[signer_index] = alpha - cees[signer_index] * aggregated_private_key;
+
+    Ok(Clsag {
+        ring,
+        commitments,
+        message,
+        c0: cees[0],
+        responses,
+        key_image,
+        auxiliary_key_image,
+        linkability_flag,
+    })
+}
+
+/// Verifies a CLSAG signature.
+/// Returns `true` if the signature is valid, `false` otherwise.
+pub fn verify_clsag(signature: Clsag) -> bool {
+    if signature.ring.is_empty() || signature.ring.len() != signature.responses.len() {
+        return false;
+    }
+    match (&signature.commitments, &signature.auxiliary_key_image) {
+        (Some(commitments), Some(_)) if commitments.len() == signature.ring.len() => {}
+        (None, None) => {}
+        _ => return false,
+    }
+
+    let transcript = Transcript::new(
+        &signature.ring,
+        signature.commitments.as_deref(),
+        &signature.message,
+        &signature.key_image,
+        signature.auxiliary_key_image.as_ref(),
+    );
+
+    // Compute the c values: c1', c2', ..., cn', c0'
+    let mut last_computed_c = signature.c0;
+    for (i, response) in signature.responses.iter().enumerate() {
+        last_computed_c = compute_next_c(
+            &transcript,
+            &signature.ring,
+            signature.commitments.as_deref(),
+            i,
+            response,
+            &last_computed_c,
+            &signature.linkability_flag,
+        );
+    }
+
+    // Return true if c0 == c0'
+    signature.c0 == last_computed_c
+}
+
+/// Computes the 'cee' value following the ring member of index `previous_index`
+fn compute_next_c(
+    transcript: &Transcript,
+    ring: &[AffinePoint],
+    commitments: Option<&[AffinePoint]>,
+    previous_index: usize,
+    previous_r: &Scalar,
+    previous_c: &Scalar,
+    linkability_flag: &Option<String>,
+) -> Scalar {
+    let public_key = &ring[previous_index];
+    let aggregated_key = transcript.aggregated_key(
+        public_key,
+        commitments.map(|commitments| &commitments[previous_index]),
+    );
+    let mapped = compute_mapped_point::<Secp256k1>(public_key, linkability_flag);
+
+    transcript.compute_c(
+        &Secp256k1::lincomb(
+            &Secp256k1::generator(),
+            previous_r,
+            &aggregated_key,
+            previous_c,
+        ),
+        &Secp256k1::lincomb(
+            &mapped,
+            previous_r,
+            &transcript.aggregated_image,
+            previous_c,
+        ),
+    )
+}
+
+fn hash_to_scalar(content: String) -> Scalar {
+    Secp256k1::scalar_from_hash(&Keccak256::digest(content.as_bytes()))
+}
+
+fn parse_points(points: &[String]) -> Result<Vec<AffinePoint>, String> {
+    points
+        .iter()
+        .map(|point| Secp256k1::deserialize_point(point))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::utils::test_utils::{get_points, get_sorted_ring};
+
+    #[test]
+    fn test_sign_clsag() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let flag = Some("linkability flag".to_string());
+
+        let signature = sign_clsag(
+            &ring,
+            Scalar::from(42u64),
+            "message".to_string(),
+            flag.clone(),
+        )
+        .expect("failed to sign");
+        assert_eq!(signature.ring.len(), 5);
+        assert!(verify_clsag(signature.clone()));
+        assert_eq!(
+            Clsag::from_base64(&signature.to_base64()),
+            Ok(signature.clone())
+        );
+
+        // CLSAG and LSAG signatures of the same key and flag are linked
+        let lsag = sign_lsag(&ring, Scalar::from(42u64), "other".to_string(), flag).unwrap();
+        assert_eq!(signature.key_image, lsag.key_image);
+
+        // a signature without flag is parsed back without flag
+        let unflagged = sign_clsag(&ring, Scalar::from(42u64), "message".to_string(), None)
+            .expect("failed to sign");
+        assert_eq!(Clsag::from_json(&unflagged.to_json()), Ok(unflagged));
+
+        let mut tampered = signature;
+        tampered.message = "another message".to_string();
+        assert!(!verify_clsag(tampered));
+    }
+
+    #[test]
+    fn test_sign_clsag_with_commitments() {
+        let ring = get_points(&[2, 3, 42, 5]);
+        let commitments = get_points(&[7, 11, 13, 17]);
+
+        let signature = sign_clsag_with_commitments(
+            &ring,
+            &commitments,
+            Scalar::from(42u64),
+            Scalar::from(13u64),
+            "message".to_string(),
+            Some("flag".to_string()),
+        )
+        .expect("failed to sign");
+        assert_eq!(signature.responses.len(), 4);
+        assert!(verify_clsag(signature.clone()));
+
+        let json = signature.to_json();
+        assert!(json.contains("auxiliaryKeyImage"));
+        assert_eq!(Clsag::from_json(&json), Ok(signature.clone()));
+
+        let mut other_commitments = signature.clone();
+        other_commitments.commitments = Some(get_points(&[7, 11, 14, 17]));
+        assert!(!verify_clsag(other_commitments));
+
+        let mut missing_commitments = signature;
+        missing_commitments.commitments = None;
+        assert!(!verify_clsag(missing_commitments));
+
+        // the commitment private key must match the signer commitment
+        assert!(sign_clsag_with_commitments(
+            &ring,
+            &commitments,
+            Scalar::from(42u64),
+            Scalar::from(7u64),
+            "message".to_string(),
+            None,
+        )
+        .is_err());
+    }
+}
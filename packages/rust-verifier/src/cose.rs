@@ -0,0 +1,358 @@
+use crate::binary_encoding::{RingSignature, Scheme};
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::verify_lsag;
+use crate::sag_signer::sign_sag;
+use crate::sag_verifier::verify_sag;
+use crate::utils::curve::Curve;
+use crate::utils::signature_config::HashFunction;
+use ciborium::value::Value;
+use k256::{AffinePoint, Scalar};
+
+/// CBOR tag of a COSE_Sign1 structure
+pub const COSE_SIGN1_TAG: u64 = 18;
+/// Private-use COSE algorithm identifier for LSAG over secp256k1 with keccak256
+pub const COSE_ALG_LSAG_SECP256K1: i64 = -65601;
+/// Private-use COSE algorithm identifier for SAG over secp256k1 with keccak256
+pub const COSE_ALG_SAG_SECP256K1: i64 = -65602;
+
+/// COSE header label of the algorithm
+const HEADER_ALG: i64 = 1;
+/// Protected header label of the curve
+pub const HEADER_CURVE: &str = "curve";
+/// Protected header label of the hash function
+pub const HEADER_HASH: &str = "hash";
+/// Protected header label of the linkability flag (true for LSAG)
+pub const HEADER_LINKABLE: &str = "linkable";
+
+/// The protected header of a ring signature COSE_Sign1 structure
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtectedHeader {
+    pub alg: i64,
+    pub curve: Curve,
+    pub hash: HashFunction,
+    pub linkable: bool,
+}
+
+impl ProtectedHeader {
+    /// Returns the protected header used for a signature scheme
+    pub fn for_scheme(scheme: Scheme) -> ProtectedHeader {
+        ProtectedHeader {
+            alg: match scheme {
+                Scheme::Lsag => COSE_ALG_LSAG_SECP256K1,
+                Scheme::Sag => COSE_ALG_SAG_SECP256K1,
+            },
+            curve: Curve::Secp256k1,
+            hash: HashFunction::Keccak256,
+            linkable: scheme == Scheme::Lsag,
+        }
+    }
+
+    /// Encodes the header as a CBOR map
+    fn to_bytes(&self) -> Vec<u8> {
+        to_cbor(&Value::Map(vec![
+            (
+                Value::Integer(HEADER_ALG.into()),
+                Value::Integer(self.alg.into()),
+            ),
+            (
+                Value::Text(HEADER_CURVE.to_string()),
+                Value::Text(self.curve.name().to_string()),
+            ),
+            (
+                Value::Text(HEADER_HASH.to_string()),
+                Value::Text(self.hash.name().to_string()),
+            ),
+            (
+                Value::Text(HEADER_LINKABLE.to_string()),
+                Value::Bool(self.linkable),
+            ),
+        ]))
+    }
+
+    /// Decodes the header from a CBOR map
+    fn from_bytes(bytes: &[u8]) -> Result<ProtectedHeader, String> {
+        let entries = match from_cbor(bytes)? {
+            Value::Map(entries) => entries,
+            _ => return Err("Protected header must be a map".to_string()),
+        };
+
+        let get = |label: Value| {
+            entries
+                .iter()
+                .find(|(key, _)| *key == label)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("Missing protected header: {:?}", label))
+        };
+
+        let alg = get(Value::Integer(HEADER_ALG.into()))?
+            .as_integer()
+            .and_then(|alg| i64::try_from(alg).ok())
+            .ok_or_else(|| "Invalid alg header".to_string())?;
+        let curve = get(Value::Text(HEADER_CURVE.to_string()))?
+            .as_text()
+            .ok_or_else(|| "Invalid curve header".to_string())?;
+        let hash = get(Value::Text(HEADER_HASH.to_string()))?
+            .as_text()
+            .ok_or_else(|| "Invalid hash header".to_string())?;
+        let linkable = get(Value::Text(HEADER_LINKABLE.to_string()))?
+            .as_bool()
+            .ok_or_else(|| "Invalid linkable header".to_string())?;
+
+        Ok(ProtectedHeader {
+            alg,
+            curve: Curve::from_name(curve)?,
+            hash: HashFunction::from_name(hash)?,
+            linkable,
+        })
+    }
+}
+
+/// A ring signature wrapped in a COSE_Sign1-like structure:
+/// `18([protected, {}, payload / nil, signature])`.
+///
+/// The signature is the binary encoding of the ring signature, without its message.
+/// The signed message is the hex encoded COSE `Sig_structure`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoseSign1 {
+    pub protected: ProtectedHeader,
+    /// None if the payload is detached
+    pub payload: Option<Vec<u8>>,
+    pub signature: RingSignature,
+    /// The protected header as it was encoded, used to compute the signed message
+    protected_bytes: Vec<u8>,
+}
+
+impl CoseSign1 {
+    /// Encodes the structure as tagged CBOR
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut signature = self.signature.clone();
+        set_message(&mut signature, String::new());
+
+        Ok(to_cbor(&Value::Tag(
+            COSE_SIGN1_TAG,
+            Box::new(Value::Array(vec![
+                Value::Bytes(self.protected_bytes.clone()),
+                Value::Map(vec![]),
+                match &self.payload {
+                    Some(payload) => Value::Bytes(payload.clone()),
+                    None => Value::Null,
+                },
+                Value::Bytes(signature.to_bytes()?),
+            ])),
+        )))
+    }
+
+    /// Decodes the structure from tagged CBOR.
+    /// The message of the decoded signature is only set once the structure is verified.
+    pub fn from_bytes(bytes: &[u8]) -> Result<CoseSign1, String> {
+        let items = match from_cbor(bytes)? {
+            Value::Tag(COSE_SIGN1_TAG, content) => match *content {
+                Value::Array(items) if items.len() == 4 => items,
+                _ => return Err("COSE_Sign1 must be an array of 4 items".to_string()),
+            },
+            _ => return Err("Missing COSE_Sign1 tag".to_string()),
+        };
+
+        let protected_bytes = items[0]
+            .as_bytes()
+            .ok_or_else(|| "Protected header must be a byte string".to_string())?
+            .clone();
+        let payload = match &items[2] {
+            Value::Bytes(payload) => Some(payload.clone()),
+            Value::Null => None,
+            _ => return Err("Payload must be a byte string or nil".to_string()),
+        };
+        let signature = items[3]
+            .as_bytes()
+            .ok_or_else(|| "Signature must be a byte string".to_string())?;
+
+        Ok(CoseSign1 {
+            protected: ProtectedHeader::from_bytes(&protected_bytes)?,
+            payload,
+            signature: RingSignature::from_bytes(signature)?,
+            protected_bytes,
+        })
+    }
+
+    /// Returns the message signed by the ring signature for a given payload
+    fn signed_message(&self, payload: &[u8]) -> String {
+        to_be_signed(&self.protected_bytes, payload)
+    }
+}
+
+/// Computes the message signed by the ring signature: the hex encoded COSE `Sig_structure`
+/// `["Signature1", protected, external_aad, payload]`
+fn to_be_signed(protected_bytes: &[u8], payload: &[u8]) -> String {
+    hex::encode(to_cbor(&Value::Array(vec![
+        Value::Text("Signature1".to_string()),
+        Value::Bytes(protected_bytes.to_vec()),
+        Value::Bytes(vec![]),
+        Value::Bytes(payload.to_vec()),
+    ])))
+}
+
+/// Signs a payload with a LSAG and wraps the signature in a COSE_Sign1 structure.
+/// If `detached` is true, the payload is not included in the structure.
+pub fn sign_lsag_cose(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    payload: &[u8],
+    linkability_flag: Option<String>,
+    detached: bool,
+) -> Result<Vec<u8>, String> {
+    let protected = ProtectedHeader::for_scheme(Scheme::Lsag);
+    let protected_bytes = protected.to_bytes();
+
+    let signature = sign_lsag(
+        ring,
+        signer_private_key,
+        to_be_signed(&protected_bytes, payload),
+        linkability_flag,
+    )?;
+
+    CoseSign1 {
+        protected,
+        payload: (!detached).then(|| payload.to_vec()),
+        signature: RingSignature::Lsag(signature),
+        protected_bytes,
+    }
+    .to_bytes()
+}
+
+/// Signs a payload with a SAG and wraps the signature in a COSE_Sign1 structure.
+/// If `detached` is true, the payload is not included in the structure.
+pub fn sign_sag_cose(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    payload: &[u8],
+    detached: bool,
+) -> Result<Vec<u8>, String> {
+    let protected = ProtectedHeader::for_scheme(Scheme::Sag);
+    let protected_bytes = protected.to_bytes();
+
+    let signature = sign_sag(
+        ring,
+        signer_private_key,
+        to_be_signed(&protected_bytes, payload),
+        None,
+    )?;
+
+    CoseSign1 {
+        protected,
+        payload: (!detached).then(|| payload.to_vec()),
+        signature: RingSignature::Sag(signature),
+        protected_bytes,
+    }
+    .to_bytes()
+}
+
+/// Verifies a COSE_Sign1 ring signature.
+/// `detached_payload` must be set if and only if the payload is detached.
+/// Returns the verified structure, with the message of the ring signature set.
+pub fn verify_cose(cose: &[u8], detached_payload: Option<&[u8]>) -> Result<CoseSign1, String> {
+    let mut cose_sign1 = CoseSign1::from_bytes(cose)?;
+
+    let payload = match (&cose_sign1.payload, detached_payload) {
+        (Some(payload), None) => payload.clone(),
+        (None, Some(payload)) => payload.to_vec(),
+        (Some(_), Some(_)) => return Err("The payload is not detached".to_string()),
+        (None, None) => return Err("Missing detached payload".to_string()),
+    };
+
+    // the protected header must match the signature
+    if cose_sign1.protected != ProtectedHeader::for_scheme(cose_sign1.signature.scheme()) {
+        return Err("Protected header does not match the signature".to_string());
+    }
+
+    let message = cose_sign1.signed_message(&payload);
+    set_message(&mut cose_sign1.signature, message);
+
+    let is_valid = match &cose_sign1.signature {
+        RingSignature::Lsag(signature) => verify_lsag(signature.clone()),
+        RingSignature::Sag(signature) => verify_sag(signature.clone()),
+    };
+    if !is_valid {
+        return Err("Invalid ring signature".to_string());
+    }
+
+    Ok(cose_sign1)
+}
+
+fn set_message(signature: &mut RingSignature, message: String) {
+    match signature {
+        RingSignature::Lsag(signature) => signature.message = message,
+        RingSignature::Sag(signature) => signature.message = message,
+    }
+}
+
+fn to_cbor(value: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes).unwrap();
+    bytes
+}
+
+fn from_cbor(bytes: &[u8]) -> Result<Value, String> {
+    ciborium::de::from_reader(bytes).map_err(|e| format!("Invalid CBOR: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_lsag_cose() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let payload = b"sensor reading: 42";
+
+        let cose = sign_lsag_cose(
+            &ring,
+            Scalar::from(3u64),
+            payload,
+            Some("flag".to_string()),
+            false,
+        )
+        .expect("failed to sign");
+
+        let verified = verify_cose(&cose, None).expect("failed to verify");
+        assert_eq!(verified.payload, Some(payload.to_vec()));
+        assert!(verified.protected.linkable);
+        match verified.signature {
+            RingSignature::Lsag(signature) => {
+                assert_eq!(signature.linkability_flag, Some("flag".to_string()))
+            }
+            RingSignature::Sag(_) => panic!("expected a LSAG"),
+        }
+
+        // the payload is not detached
+        assert!(verify_cose(&cose, Some(payload)).is_err());
+
+        // tampered payload
+        let mut tampered = CoseSign1::from_bytes(&cose).unwrap();
+        tampered.payload = Some(b"sensor reading: 43".to_vec());
+        assert!(verify_cose(&tampered.to_bytes().unwrap(), None).is_err());
+    }
+
+    #[test]
+    fn test_detached_sag_cose() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let payload = b"firmware image";
+
+        let cose = sign_sag_cose(&ring, Scalar::from(5u64), payload, true).unwrap();
+
+        let decoded = CoseSign1::from_bytes(&cose).unwrap();
+        assert_eq!(decoded.payload, None);
+        assert_eq!(decoded.protected.alg, COSE_ALG_SAG_SECP256K1);
+        assert!(!decoded.protected.linkable);
+
+        assert!(verify_cose(&cose, Some(payload)).is_ok());
+        assert!(verify_cose(&cose, Some(b"another image")).is_err());
+        assert!(verify_cose(&cose, None).is_err());
+    }
+
+    #[test]
+    fn test_invalid_cose() {
+        assert!(CoseSign1::from_bytes(&[0x00]).is_err());
+        assert!(verify_cose(&to_cbor(&Value::Array(vec![])), None).is_err());
+    }
+}
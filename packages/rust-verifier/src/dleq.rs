@@ -0,0 +1,270 @@
+//! DLEQ (Chaum-Pedersen) proofs: `P = x * G` and `I = x * H` share the same `x`, without
+//! revealing it.
+//!
+//! With `H = H(P, flag)`, the mapped point of the LSAG signatures, `I` is the key image of `P`
+//! under `flag`, so a key image can be published and proven apart from a signature.
+//!
+//! The proofs are non-interactive (Fiat-Shamir): the challenge hashes the points and a context
+//! string binding the proof to its use, with a configurable hash function. Points and scalars
+//! are serialized as in the signatures.
+use crate::lsag_verifier::compute_mapped_point;
+use crate::utils::curve::RingCurve;
+use crate::utils::signature_config::HashFunction;
+use elliptic_curve::bigint::U512;
+use elliptic_curve::ops::Reduce;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use sha3::{Digest, Keccak256};
+
+const PROOF_DOMAIN: &str = "dleq";
+
+/// The JSON representation of a DLEQ proof
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StringifiedDleqProof {
+    pub c: String,
+    pub response: String,
+    pub hash: HashFunction,
+}
+
+/// A DLEQ proof: the challenge and response of a Chaum-Pedersen proof
+#[derive(Clone, Debug, PartialEq)]
+pub struct DleqProof {
+    pub c: Scalar,
+    pub response: Scalar,
+    /// The hash function of the Fiat-Shamir challenge
+    pub hash: HashFunction,
+}
+
+impl DleqProof {
+    /// Parses a proof from its JSON string
+    pub fn from_json(json: &str) -> Result<DleqProof, String> {
+        let stringified: StringifiedDleqProof =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        DleqProof::try_from(stringified)
+    }
+
+    /// Converts the proof to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedDleqProof::from(self)).unwrap()
+    }
+}
+
+impl TryFrom<StringifiedDleqProof> for DleqProof {
+    type Error = String;
+
+    fn try_from(json: StringifiedDleqProof) -> Result<DleqProof, String> {
+        Ok(DleqProof {
+            c: Secp256k1::scalar_from_hex(&json.c)?,
+            response: Secp256k1::scalar_from_hex(&json.response)?,
+            hash: json.hash,
+        })
+    }
+}
+
+impl From<&DleqProof> for StringifiedDleqProof {
+    fn from(proof: &DleqProof) -> StringifiedDleqProof {
+        StringifiedDleqProof {
+            c: Secp256k1::scalar_to_hex(&proof.c),
+            response: Secp256k1::scalar_to_hex(&proof.response),
+            hash: proof.hash,
+        }
+    }
+}
+
+/// Proves that `secret * G` and `secret * base` share the same discrete logarithm
+pub fn prove_dleq(
+    secret: &Scalar,
+    base: &AffinePoint,
+    context: &str,
+    hash: HashFunction,
+) -> DleqProof {
+    let public_key = Secp256k1::mul_generator(secret);
+    let image = Secp256k1::mul(base, secret);
+
+    let nonce = Secp256k1::random_scalar();
+    let c = compute_challenge(
+        base,
+        &public_key,
+        &image,
+        &Secp256k1::mul_generator(&nonce),
+        &Secp256k1::mul(base, &nonce),
+        context,
+        hash,
+    );
+
+    DleqProof {
+        c,
+        response: nonce - c * secret,
+        hash,
+    }
+}
+
+/// Verifies that `public_key = x * G` and `image = x * base` for the same `x`.
+/// Returns `true` if the proof is valid, `false` otherwise.
+pub fn verify_dleq(
+    proof: &DleqProof,
+    public_key: &AffinePoint,
+    base: &AffinePoint,
+    image: &AffinePoint,
+    context: &str,
+) -> bool {
+    // A = s*G + c*P and B = s*H + c*I
+    let commitment_g = Secp256k1::lincomb(
+        &Secp256k1::generator(),
+        &proof.response,
+        public_key,
+        &proof.c,
+    );
+    let commitment_h = Secp256k1::lincomb(base, &proof.response, image, &proof.c);
+
+    compute_challenge(
+        base,
+        public_key,
+        image,
+        &commitment_g,
+        &commitment_h,
+        context,
+        proof.hash,
+    ) == proof.c
+}
+
+/// Computes the key image of `secret` under `linkability_flag`, as in the LSAG signatures,
+/// and proves that it matches the public key `secret * G`
+pub fn prove_key_image(
+    secret: &Scalar,
+    linkability_flag: &Option<String>,
+    context: &str,
+    hash: HashFunction,
+) -> (AffinePoint, DleqProof) {
+    let mapped =
+        compute_mapped_point::<Secp256k1>(&Secp256k1::mul_generator(secret), linkability_flag);
+
+    (
+        Secp256k1::mul(&mapped, secret),
+        prove_dleq(secret, &mapped, context, hash),
+    )
+}
+
+/// Verifies that `key_image` is the key image of `public_key` under `linkability_flag`.
+/// Returns `true` if the proof is valid, `false` otherwise.
+pub fn verify_key_image(
+    proof: &DleqProof,
+    public_key: &AffinePoint,
+    key_image: &AffinePoint,
+    linkability_flag: &Option<String>,
+    context: &str,
+) -> bool {
+    let mapped = compute_mapped_point::<Secp256k1>(public_key, linkability_flag);
+
+    verify_dleq(proof, public_key, &mapped, key_image, context)
+}
+
+/// Computes the Fiat-Shamir challenge of a proof
+fn compute_challenge(
+    base: &AffinePoint,
+    public_key: &AffinePoint,
+    image: &AffinePoint,
+    commitment_g: &AffinePoint,
+    commitment_h: &AffinePoint,
+    context: &str,
+    hash: HashFunction,
+) -> Scalar {
+    let hash_content = PROOF_DOMAIN.to_string()
+        + &Secp256k1::serialize_point(base)
+        + &Secp256k1::serialize_point(public_key)
+        + &Secp256k1::serialize_point(image)
+        + &Secp256k1::serialize_point(commitment_g)
+        + &Secp256k1::serialize_point(commitment_h)
+        + context;
+
+    match hash {
+        HashFunction::Keccak256 => {
+            Secp256k1::scalar_from_hash(&Keccak256::digest(hash_content.as_bytes()))
+        }
+        HashFunction::Sha256 => {
+            Secp256k1::scalar_from_hash(&Sha256::digest(hash_content.as_bytes()))
+        }
+        HashFunction::Sha512 => {
+            <Scalar as Reduce<U512>>::reduce_bytes(&Sha512::digest(hash_content.as_bytes()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_image_proof() {
+        let secret = Scalar::from(42u64);
+        let public_key = Secp256k1::mul_generator(&secret);
+        let flag = Some("poll 1".to_string());
+
+        for hash in [
+            HashFunction::Keccak256,
+            HashFunction::Sha256,
+            HashFunction::Sha512,
+        ] {
+            let (key_image, proof) = prove_key_image(&secret, &flag, "context", hash);
+            assert!(verify_key_image(
+                &proof,
+                &public_key,
+                &key_image,
+                &flag,
+                "context"
+            ));
+            assert_eq!(DleqProof::from_json(&proof.to_json()), Ok(proof.clone()));
+
+            // the proof is bound to the flag and the context
+            assert!(!verify_key_image(
+                &proof,
+                &public_key,
+                &key_image,
+                &None,
+                "context"
+            ));
+            assert!(!verify_key_image(
+                &proof,
+                &public_key,
+                &key_image,
+                &flag,
+                "other"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_dleq() {
+        let base = Secp256k1::hash_to_curve("base".to_string());
+        let proof = prove_dleq(&Scalar::from(3u64), &base, "", HashFunction::Keccak256);
+
+        let public_key = Secp256k1::mul_generator(&Scalar::from(3u64));
+        assert!(verify_dleq(
+            &proof,
+            &public_key,
+            &base,
+            &Secp256k1::mul(&base, &Scalar::from(3u64)),
+            ""
+        ));
+        // the discrete logarithms differ
+        assert!(!verify_dleq(
+            &proof,
+            &public_key,
+            &base,
+            &Secp256k1::mul(&base, &Scalar::from(4u64)),
+            ""
+        ));
+
+        let mut other_hash = proof;
+        other_hash.hash = HashFunction::Sha256;
+        assert!(!verify_dleq(
+            &other_hash,
+            &public_key,
+            &base,
+            &Secp256k1::mul(&base, &Scalar::from(3u64)),
+            ""
+        ));
+    }
+}
@@ -0,0 +1,420 @@
+//! Recovers secp256k1 public keys from Ethereum signatures, to build rings from addresses.
+//!
+//! Supported signatures:
+//! - RLP-encoded signed transactions: legacy (with or without EIP-155 replay protection),
+//!   EIP-2930 (type 1) and EIP-1559 (type 2)
+//! - `personal_sign` (EIP-191) signatures
+use crate::ring::Ring;
+use crate::utils::eth_address::{eth_address_bytes, parse_eth_address};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use k256::AffinePoint;
+use sha3::{Digest, Keccak256};
+
+/// A signature from which the signer public key can be recovered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EthereumSignature {
+    /// A RLP-encoded signed transaction, as returned by `eth_getRawTransactionByHash`
+    Transaction(Vec<u8>),
+    /// A `personal_sign` signature (`r || s || v`, 65 bytes) of `message`
+    PersonalSign {
+        message: Vec<u8>,
+        signature: Vec<u8>,
+    },
+}
+
+impl EthereumSignature {
+    /// Recovers the signer public key
+    pub fn recover(&self) -> Result<AffinePoint, String> {
+        match self {
+            EthereumSignature::Transaction(raw_transaction) => {
+                recover_from_transaction(raw_transaction)
+            }
+            EthereumSignature::PersonalSign { message, signature } => {
+                recover_from_personal_sign(message, signature)
+            }
+        }
+    }
+}
+
+/// A ring member, known by its address, and a signature proving its public key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthereumRingMember {
+    /// The 0x-prefixed hex address of the member
+    pub address: String,
+    pub signature: EthereumSignature,
+}
+
+/// Recovers the public keys of the members and builds their ring.
+/// Returns an error if a recovered public key does not match the expected address.
+pub fn ring_from_ethereum_signatures(members: &[EthereumRingMember]) -> Result<Ring, String> {
+    let public_keys = members
+        .iter()
+        .map(|member| {
+            let public_key = member.signature.recover()?;
+
+            if eth_address_bytes(&public_key) != parse_eth_address(&member.address)? {
+                return Err(format!(
+                    "The recovered public key does not match the address {}",
+                    member.address
+                ));
+            }
+            Ok(public_key)
+        })
+        .collect::<Result<Vec<AffinePoint>, String>>()?;
+
+    Ring::new(public_keys)
+}
+
+/// Recovers the signer public key of a RLP-encoded signed transaction
+pub fn recover_from_transaction(raw_transaction: &[u8]) -> Result<AffinePoint, String> {
+    let first_byte = *raw_transaction
+        .first()
+        .ok_or_else(|| "Empty transaction".to_string())?;
+
+    match first_byte {
+        // legacy transaction: rlp([nonce, gasPrice, gasLimit, to, value, data, v, r, s])
+        0xc0..=0xff => {
+            let fields = decode_list(raw_transaction)?;
+            if fields.len() != 9 {
+                return Err("A legacy transaction must have 9 fields".to_string());
+            }
+
+            let v = decode_u64(fields[6].1)?;
+            let (mut unsigned_fields, recovery_id) = match v {
+                27 | 28 => (raw_items(&fields[..6]), (v - 27) as u8),
+                // EIP-155: v = chainId * 2 + 35 + yParity
+                v if v >= 35 => {
+                    let mut unsigned_fields = raw_items(&fields[..6]);
+                    let chain_id = (v - 35) / 2;
+                    unsigned_fields
+                        .extend(encode_bytes(trim_leading_zeros(&chain_id.to_be_bytes())));
+                    unsigned_fields.extend(encode_bytes(&[]));
+                    unsigned_fields.extend(encode_bytes(&[]));
+                    (unsigned_fields, ((v - 35) % 2) as u8)
+                }
+                _ => return Err(format!("Invalid legacy transaction v value: {}", v)),
+            };
+            unsigned_fields = encode_list(&unsigned_fields);
+
+            recover(
+                &Keccak256::digest(&unsigned_fields).into(),
+                fields[7].1,
+                fields[8].1,
+                recovery_id,
+            )
+        }
+        // typed transactions: type || rlp([...fields, yParity, r, s])
+        0x01 | 0x02 => {
+            let fields = decode_list(&raw_transaction[1..])?;
+            let fields_count = if first_byte == 0x01 { 11 } else { 12 };
+            if fields.len() != fields_count {
+                return Err(format!(
+                    "A type {} transaction must have {} fields",
+                    first_byte, fields_count
+                ));
+            }
+
+            let y_parity = decode_u64(fields[fields_count - 3].1)?;
+            if y_parity > 1 {
+                return Err(format!("Invalid y parity: {}", y_parity));
+            }
+
+            let mut signing_payload = vec![first_byte];
+            signing_payload.extend(encode_list(&raw_items(&fields[..fields_count - 3])));
+
+            recover(
+                &Keccak256::digest(&signing_payload).into(),
+                fields[fields_count - 2].1,
+                fields[fields_count - 1].1,
+                y_parity as u8,
+            )
+        }
+        _ => Err(format!("Unsupported transaction type: {}", first_byte)),
+    }
+}
+
+/// Recovers the signer public key of a `personal_sign` signature
+pub fn recover_from_personal_sign(message: &[u8], signature: &[u8]) -> Result<AffinePoint, String> {
+    if signature.len() != 65 {
+        return Err("A personal_sign signature must be 65 bytes long".to_string());
+    }
+
+    let recovery_id = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        v => return Err(format!("Invalid signature v value: {}", v)),
+    };
+
+    recover(
+        &personal_sign_hash(message),
+        &signature[..32],
+        &signature[32..64],
+        recovery_id,
+    )
+}
+
+/// Returns the EIP-191 hash of a message:
+/// `keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)`
+pub fn personal_sign_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// ecrecover
+fn recover(hash: &[u8; 32], r: &[u8], s: &[u8], recovery_id: u8) -> Result<AffinePoint, String> {
+    let signature = Signature::from_scalars(to_32_bytes(r)?, to_32_bytes(s)?)
+        .map_err(|_| "Invalid signature".to_string())?;
+
+    // k256 only accepts low s signatures: normalizing s negates the nonce point
+    let (signature, recovery_id) = match signature.normalize_s() {
+        Some(normalized) => (normalized, recovery_id ^ 1),
+        None => (signature, recovery_id),
+    };
+    let recovery_id =
+        RecoveryId::from_byte(recovery_id).ok_or_else(|| "Invalid recovery id".to_string())?;
+
+    let verifying_key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id)
+        .map_err(|_| "Failed to recover the public key".to_string())?;
+
+    Ok(*verifying_key.as_affine())
+}
+
+fn to_32_bytes(bytes: &[u8]) -> Result<[u8; 32], String> {
+    if bytes.len() > 32 {
+        return Err("Signature values must be at most 32 bytes long".to_string());
+    }
+
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(bytes);
+    Ok(padded)
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let first_non_zero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[first_non_zero..]
+}
+
+/// A decoded RLP item: its full encoding and its payload
+type RlpItem<'a> = (&'a [u8], &'a [u8]);
+
+/// Decodes the header of a RLP item. Returns whether the item is a list,
+/// the payload offset and the payload length.
+fn decode_header(bytes: &[u8]) -> Result<(bool, usize, usize), String> {
+    let prefix = *bytes
+        .first()
+        .ok_or_else(|| "Unexpected end of RLP data".to_string())?;
+
+    let (is_list, offset, length) = match prefix {
+        0x00..=0x7f => (false, 0, 1),
+        0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+        0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+        _ => {
+            let is_list = prefix >= 0xf8;
+            let length_size = (prefix - if is_list { 0xf7 } else { 0xb7 }) as usize;
+            let length_bytes = bytes
+                .get(1..1 + length_size)
+                .ok_or_else(|| "Unexpected end of RLP data".to_string())?;
+            if length_size > 8 {
+                return Err("RLP item too long".to_string());
+            }
+
+            let length = length_bytes
+                .iter()
+                .fold(0u64, |length, byte| (length << 8) | *byte as u64);
+            (is_list, 1 + length_size, length as usize)
+        }
+    };
+
+    if offset
+        .checked_add(length)
+        .is_none_or(|end| end > bytes.len())
+    {
+        return Err("Unexpected end of RLP data".to_string());
+    }
+    Ok((is_list, offset, length))
+}
+
+/// Decodes a RLP list spanning the whole input, whose items must be byte strings or lists
+fn decode_list(bytes: &[u8]) -> Result<Vec<RlpItem<'_>>, String> {
+    let (is_list, offset, length) = decode_header(bytes)?;
+    if !is_list || offset + length != bytes.len() {
+        return Err("Invalid RLP list".to_string());
+    }
+
+    let mut items = Vec::new();
+    let mut remaining = &bytes[offset..];
+    while !remaining.is_empty() {
+        let (_, item_offset, item_length) = decode_header(remaining)?;
+        let (item, rest) = remaining.split_at(item_offset + item_length);
+        items.push((item, &item[item_offset..]));
+        remaining = rest;
+    }
+
+    Ok(items)
+}
+
+fn decode_u64(bytes: &[u8]) -> Result<u64, String> {
+    if bytes.len() > 8 {
+        return Err("RLP integer too large".to_string());
+    }
+    Ok(bytes
+        .iter()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+}
+
+fn raw_items(items: &[RlpItem]) -> Vec<u8> {
+    items.iter().flat_map(|(raw, _)| raw.to_vec()).collect()
+}
+
+fn encode_header(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+
+    let length_bytes = trim_leading_zeros(&length.to_be_bytes()).to_vec();
+    let mut header = vec![offset + 55 + length_bytes.len() as u8];
+    header.extend(length_bytes);
+    header
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+
+    let mut encoded = encode_header(bytes.len(), 0x80);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+/// Encodes a list from the concatenated encodings of its items
+fn encode_list(items: &[u8]) -> Vec<u8> {
+    let mut encoded = encode_header(items.len(), 0xc0);
+    encoded.extend_from_slice(items);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::eth_address::to_eth_address;
+    use k256::ecdsa::SigningKey;
+
+    /// The EIP-155 example transaction, signed with the private key 0x4646...46
+    const EIP_155_TRANSACTION: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const EIP_155_ADDRESS: &str = "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F";
+
+    fn signing_key(private_key: u8) -> SigningKey {
+        SigningKey::from_bytes(&[private_key; 32].into()).unwrap()
+    }
+
+    /// Signs a typed transaction with the given unsigned fields
+    fn sign_typed_transaction(key: &SigningKey, tx_type: u8, fields: &[Vec<u8>]) -> Vec<u8> {
+        let mut items: Vec<u8> = fields
+            .iter()
+            .flat_map(|field| encode_bytes(field))
+            .collect();
+        // empty access list
+        items.extend(encode_list(&[]));
+
+        let mut signing_payload = vec![tx_type];
+        signing_payload.extend(encode_list(&items));
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&Keccak256::digest(&signing_payload))
+            .unwrap();
+
+        items.extend(encode_bytes(trim_leading_zeros(&[recovery_id.to_byte()])));
+        items.extend(encode_bytes(trim_leading_zeros(&signature.r().to_bytes())));
+        items.extend(encode_bytes(trim_leading_zeros(&signature.s().to_bytes())));
+
+        let mut transaction = vec![tx_type];
+        transaction.extend(encode_list(&items));
+        transaction
+    }
+
+    #[test]
+    fn test_recover_from_legacy_transaction() {
+        let public_key =
+            recover_from_transaction(&hex::decode(EIP_155_TRANSACTION).unwrap()).unwrap();
+
+        assert_eq!(public_key, *signing_key(0x46).verifying_key().as_affine());
+        assert_eq!(to_eth_address(&public_key), EIP_155_ADDRESS);
+    }
+
+    #[test]
+    fn test_recover_from_typed_transactions() {
+        let key = signing_key(0x42);
+        let to = vec![0x35; 20];
+
+        // chainId, nonce, gasPrice, gasLimit, to, value, data
+        let eip_2930_fields = [
+            vec![1],
+            vec![9],
+            vec![0x04, 0xa8, 0x17, 0xc8, 0x00],
+            vec![0x52, 0x08],
+            to.clone(),
+            vec![0x0d, 0xe0],
+            vec![],
+        ];
+        let transaction = sign_typed_transaction(&key, 0x01, &eip_2930_fields);
+        assert_eq!(
+            recover_from_transaction(&transaction),
+            Ok(*key.verifying_key().as_affine())
+        );
+
+        // chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gasLimit, to, value, data
+        let eip_1559_fields = [
+            vec![1],
+            vec![],
+            vec![0x3b, 0x9a, 0xca, 0x00],
+            vec![0x04, 0xa8, 0x17, 0xc8, 0x00],
+            vec![0x52, 0x08],
+            to,
+            vec![0x0d, 0xe0],
+            vec![0xde, 0xad, 0xbe, 0xef],
+        ];
+        let transaction = sign_typed_transaction(&key, 0x02, &eip_1559_fields);
+        assert_eq!(
+            recover_from_transaction(&transaction),
+            Ok(*key.verifying_key().as_affine())
+        );
+
+        assert!(recover_from_transaction(&transaction[..transaction.len() - 1]).is_err());
+        assert!(recover_from_transaction(&[0x03, 0xc0]).is_err());
+    }
+
+    #[test]
+    fn test_ring_from_ethereum_signatures() {
+        let key = signing_key(0x42);
+        let message = b"I am a member of the DAO".to_vec();
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&personal_sign_hash(&message))
+            .unwrap();
+        let mut personal_sign_signature = signature.to_bytes().to_vec();
+        personal_sign_signature.push(recovery_id.to_byte() + 27);
+
+        let personal_sign_member = EthereumRingMember {
+            address: to_eth_address(key.verifying_key().as_affine()),
+            signature: EthereumSignature::PersonalSign {
+                message,
+                signature: personal_sign_signature,
+            },
+        };
+        let transaction_member = EthereumRingMember {
+            address: EIP_155_ADDRESS.to_string(),
+            signature: EthereumSignature::Transaction(hex::decode(EIP_155_TRANSACTION).unwrap()),
+        };
+
+        let ring =
+            ring_from_ethereum_signatures(&[personal_sign_member.clone(), transaction_member])
+                .expect("failed to build the ring");
+        assert_eq!(ring.len(), 2);
+        assert!(ring.position(key.verifying_key().as_affine()).is_some());
+
+        let mut wrong_address = personal_sign_member;
+        wrong_address.address = EIP_155_ADDRESS.to_string();
+        assert!(ring_from_ethereum_signatures(&[wrong_address]).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+use crate::binary_encoding::{write_point, Reader};
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::{verify_lsag, Lsag};
+use crate::sag_signer::sign_sag;
+use crate::sag_verifier::{verify_sag, Sag};
+use crate::utils::serialize_point::serialize_point;
+use crate::utils::serialize_ring::deserialize_ring;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+
+/// JWS `alg` of a LSAG over secp256k1
+pub const JWS_ALG_LSAG: &str = "LSAG-K256";
+/// JWS `alg` of a SAG over secp256k1
+pub const JWS_ALG_SAG: &str = "SAG-K256";
+
+/// The JOSE header of a ring signature JWS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsHeader {
+    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// Reference to a ring known by the verifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// The embedded ring, as compressed hex public keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ring: Option<Vec<String>>,
+    /// The linkability flag of a LSAG
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkability_flag: Option<String>,
+}
+
+/// How the ring is conveyed to the verifier.
+/// In both cases, the ring must contain the signer public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JwsRing {
+    /// The ring is embedded in the header
+    Embedded,
+    /// The header only carries a `kid` referencing a ring known by the verifier
+    Reference(String),
+}
+
+/// A verified ring signature JWS
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedJws {
+    pub header: JwsHeader,
+    pub payload: Vec<u8>,
+    pub ring: Vec<AffinePoint>,
+    /// The key image of a LSAG, which can be used to rate-limit the token holder
+    pub key_image: Option<AffinePoint>,
+}
+
+impl VerifiedJws {
+    /// Parses the payload as JSON claims
+    pub fn claims(&self) -> Result<serde_json::Value, String> {
+        serde_json::from_slice(&self.payload).map_err(|e| format!("Invalid JWT claims: {}", e))
+    }
+}
+
+/// Creates a compact JWS whose signature is a LSAG over `base64url(header).base64url(payload)`
+pub fn sign_lsag_jws(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    payload: &[u8],
+    linkability_flag: Option<String>,
+    jws_ring: JwsRing,
+) -> Result<String, String> {
+    let (ring, header) = build_header(
+        ring,
+        &signer_private_key,
+        JWS_ALG_LSAG,
+        linkability_flag.clone(),
+        jws_ring,
+    )?;
+    let signing_input = signing_input(&header, payload);
+
+    let signature = sign_lsag(
+        &ring,
+        signer_private_key,
+        signing_input.clone(),
+        linkability_flag,
+    )?;
+
+    let mut signature_bytes = scalars_to_bytes(&signature.c0, &signature.responses);
+    write_point::<Secp256k1>(&mut signature_bytes, &signature.key_image);
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature_bytes)
+    ))
+}
+
+/// Creates a compact JWS whose signature is a SAG over `base64url(header).base64url(payload)`
+pub fn sign_sag_jws(
+    ring: &[AffinePoint],
+    signer_private_key: Scalar,
+    payload: &[u8],
+    jws_ring: JwsRing,
+) -> Result<String, String> {
+    let (ring, header) = build_header(ring, &signer_private_key, JWS_ALG_SAG, None, jws_ring)?;
+    let signing_input = signing_input(&header, payload);
+
+    let signature = sign_sag(&ring, signer_private_key, signing_input.clone(), None)?;
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(scalars_to_bytes(&signature.c0, &signature.responses))
+    ))
+}
+
+/// Verifies a ring signature compact JWS.
+///
+/// `ring` is the ring trusted by the verifier, e.g. the ring referenced by the `kid` header.
+/// A ring embedded in the header must be equal to it.
+///
+/// `expected_linkability_flag` is the linkability flag of the verifier, e.g. its API name.
+/// Tokens with another flag are rejected, so that a holder cannot get a new key image by
+/// choosing a new flag for each token.
+pub fn verify_jws(
+    token: &str,
+    ring: &[AffinePoint],
+    expected_linkability_flag: Option<&str>,
+) -> Result<VerifiedJws, String> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err("A compact JWS must have 3 parts".to_string());
+    }
+
+    let header: JwsHeader = serde_json::from_slice(&decode_part(parts[0])?)
+        .map_err(|e| format!("Invalid JWS header: {}", e))?;
+    let payload = decode_part(parts[1])?;
+    let signature_bytes = decode_part(parts[2])?;
+
+    if ring.is_empty() {
+        return Err("Ring cannot be empty".to_string());
+    }
+    if let Some(embedded) = &header.ring {
+        if deserialize_ring(embedded)? != ring {
+            return Err("The embedded ring does not match the expected ring".to_string());
+        }
+    }
+    if header.linkability_flag.as_deref() != expected_linkability_flag {
+        return Err("The linkability flag does not match the expected flag".to_string());
+    }
+    let ring = ring.to_vec();
+
+    let message = format!("{}.{}", parts[0], parts[1]);
+    let mut reader = Reader::new(&signature_bytes);
+    let c0 = reader.read_scalar::<Secp256k1>()?;
+    let responses = ring
+        .iter()
+        .map(|_| reader.read_scalar::<Secp256k1>())
+        .collect::<Result<Vec<Scalar>, String>>()?;
+
+    let (is_valid, key_image) = match header.alg.as_str() {
+        JWS_ALG_LSAG => {
+            let key_image = reader.read_point::<Secp256k1>()?;
+            reader.finish()?;
+
+            let signature = Lsag {
+                ring: ring.clone(),
+                message,
+                c0,
+                responses,
+                key_image,
+                linkability_flag: header.linkability_flag.clone(),
+                config: None,
+                evm_witnesses: None,
+            };
+            (verify_lsag(signature), Some(key_image))
+        }
+        JWS_ALG_SAG => {
+            reader.finish()?;
+
+            let signature = Sag {
+                ring: ring.clone(),
+                message,
+                c0,
+                responses,
+                config: None,
+            };
+            (verify_sag(signature), None)
+        }
+        alg => return Err(format!("Unsupported alg: {}", alg)),
+    };
+
+    if !is_valid {
+        return Err("Invalid ring signature".to_string());
+    }
+
+    Ok(VerifiedJws {
+        header,
+        payload,
+        ring,
+        key_image,
+    })
+}
+
+/// Checks that the signer is a member of the ring and builds the JWS header
+fn build_header(
+    ring: &[AffinePoint],
+    signer_private_key: &Scalar,
+    alg: &str,
+    linkability_flag: Option<String>,
+    jws_ring: JwsRing,
+) -> Result<(Vec<AffinePoint>, JwsHeader), String> {
+    let public_key = (AffinePoint::GENERATOR * signer_private_key).to_affine();
+    if !ring.contains(&public_key) {
+        return Err("The ring must contain the signer public key".to_string());
+    }
+
+    let (kid, embedded_ring) = match jws_ring {
+        JwsRing::Embedded => (
+            None,
+            Some(ring.iter().map(|p| serialize_point(*p)).collect()),
+        ),
+        JwsRing::Reference(kid) => (Some(kid), None),
+    };
+
+    Ok((
+        ring.to_vec(),
+        JwsHeader {
+            alg: alg.to_string(),
+            typ: Some("JWT".to_string()),
+            kid,
+            ring: embedded_ring,
+            linkability_flag,
+        },
+    ))
+}
+
+/// Returns `base64url(header).base64url(payload)`
+fn signing_input(header: &JwsHeader, payload: &[u8]) -> String {
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_string(header).unwrap()),
+        URL_SAFE_NO_PAD.encode(payload)
+    )
+}
+
+fn decode_part(part: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|e| format!("Invalid base64url string: {}", e))
+}
+
+fn scalars_to_bytes(c0: &Scalar, responses: &[Scalar]) -> Vec<u8> {
+    let mut bytes = c0.to_bytes().to_vec();
+    for response in responses {
+        bytes.extend_from_slice(&response.to_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_lsag_jws_with_embedded_ring() {
+        let ring = get_sorted_ring(&[2, 3, 4, 42]);
+        let claims = br#"{"sub":"anonymous","scope":"vote"}"#;
+        let flag = Some("api.example.com");
+
+        let token = sign_lsag_jws(
+            &ring,
+            Scalar::from(42u64),
+            claims,
+            flag.map(String::from),
+            JwsRing::Embedded,
+        )
+        .expect("failed to sign");
+
+        let verified = verify_jws(&token, &ring, flag).expect("failed to verify");
+        assert_eq!(verified.header.alg, JWS_ALG_LSAG);
+        assert_eq!(verified.ring.len(), 4);
+        assert_eq!(verified.claims().unwrap()["scope"], "vote");
+
+        // two tokens of the same holder share the same key image
+        let other_token = sign_lsag_jws(
+            &ring,
+            Scalar::from(42u64),
+            b"{}",
+            flag.map(String::from),
+            JwsRing::Embedded,
+        )
+        .unwrap();
+        assert_eq!(
+            verify_jws(&other_token, &ring, flag).unwrap().key_image,
+            verified.key_image
+        );
+
+        // the holder cannot choose another flag to get a new key image
+        let other_flag = sign_lsag_jws(
+            &ring,
+            Scalar::from(42u64),
+            b"{}",
+            Some("other".to_string()),
+            JwsRing::Embedded,
+        )
+        .unwrap();
+        assert!(verify_jws(&other_flag, &ring, flag).is_err());
+
+        // the embedded ring must be the trusted ring
+        assert!(verify_jws(&token, &ring[1..], flag).is_err());
+        let own_ring = get_sorted_ring(&[5, 6, 42]);
+        let own_ring_token = sign_lsag_jws(
+            &own_ring,
+            Scalar::from(42u64),
+            claims,
+            flag.map(String::from),
+            JwsRing::Embedded,
+        )
+        .unwrap();
+        assert!(verify_jws(&own_ring_token, &ring, flag).is_err());
+
+        // tampered payload
+        let parts: Vec<&str> = token.split('.').collect();
+        let tampered = format!(
+            "{}.{}.{}",
+            parts[0],
+            URL_SAFE_NO_PAD.encode(br#"{"sub":"anonymous","scope":"admin"}"#),
+            parts[2]
+        );
+        assert!(verify_jws(&tampered, &ring, flag).is_err());
+    }
+
+    #[test]
+    fn test_sag_jws_with_referenced_ring() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+
+        let token = sign_sag_jws(
+            &ring,
+            Scalar::from(4u64),
+            b"{}",
+            JwsRing::Reference("dao-members".to_string()),
+        )
+        .expect("failed to sign");
+
+        assert!(verify_jws(&token, &ring[1..], None).is_err());
+        let verified = verify_jws(&token, &ring, None).expect("failed to verify");
+        assert_eq!(verified.header.kid, Some("dao-members".to_string()));
+        assert_eq!(verified.key_image, None);
+
+        // the ring must contain the signer
+        assert!(sign_sag_jws(
+            &ring,
+            Scalar::from(42u64),
+            b"{}",
+            JwsRing::Reference("dao-members".to_string()),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_invalid_jws() {
+        let ring = get_sorted_ring(&[2, 3]);
+        assert!(verify_jws("a.b", &ring, None).is_err());
+        assert!(verify_jws("a.b.c", &ring, None).is_err());
+    }
+}
@@ -1,6 +1,32 @@
+pub mod binary_encoding;
+pub mod bitcoin;
+pub mod claim;
+pub mod clsag;
+pub mod cose;
+pub mod dleq;
+pub mod ethereum;
+pub mod jws;
+pub mod linking;
+pub mod lsag_signer;
 pub mod lsag_verifier;
+pub mod merkle;
+pub mod mlsag;
+pub mod nostr;
+pub mod policy;
+pub mod repudiation;
+pub mod ring;
+pub mod ring_file;
+pub mod ring_resolver;
+pub mod sag_signer;
+pub mod sag_verifier;
+pub mod ssh;
+pub mod threshold;
 pub mod utils;
 // Re-exporting k256
 pub use k256;
 // Re-exporting elliptic_curve
 pub use elliptic_curve;
+// Re-exporting p256
+pub use p256;
+// Re-exporting curve25519_dalek
+pub use curve25519_dalek;
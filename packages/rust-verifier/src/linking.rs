@@ -0,0 +1,262 @@
+//! Consent-based linking: the signer of two LSAG signatures under different linkability flags
+//! proves that both come from the same key, e.g. to carry reputation across polls, without
+//! revealing the key.
+//!
+//! Key images are `x * H(K, flag)`, so they differ across flags. The proof is a ring signature
+//! over the members of both rings: for one of them, `K = x * G`, `I_A = x * H(K, flag A)` and
+//! `I_B = x * H(K, flag B)`. It is verified with the two signatures only.
+use crate::lsag_verifier::{compute_mapped_point, verify_lsag, Lsag};
+use crate::utils::curve::RingCurve;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+const PROOF_DOMAIN: &str = "cross_flag_linking";
+
+/// The JSON representation of a linking proof
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StringifiedLinkingProof {
+    pub c: String,
+    pub responses: Vec<String>,
+}
+
+/// A proof that two LSAG signatures were signed with the same key.
+/// The ring of the proof is made of the members of both signature rings, in the order of the
+/// first signature ring, with one response per member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkingProof {
+    pub c0: Scalar,
+    pub responses: Vec<Scalar>,
+}
+
+impl LinkingProof {
+    /// Parses a proof from its JSON string
+    pub fn from_json(json: &str) -> Result<LinkingProof, String> {
+        let stringified: StringifiedLinkingProof =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        Ok(LinkingProof {
+            c0: Secp256k1::scalar_from_hex(&stringified.c)?,
+            responses: stringified
+                .responses
+                .iter()
+                .map(|response| Secp256k1::scalar_from_hex(response))
+                .collect::<Result<Vec<Scalar>, String>>()?,
+        })
+    }
+
+    /// Converts the proof to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedLinkingProof {
+            c: Secp256k1::scalar_to_hex(&self.c0),
+            responses: self
+                .responses
+                .iter()
+                .map(Secp256k1::scalar_to_hex)
+                .collect(),
+        })
+        .unwrap()
+    }
+}
+
+/// Proves that `first` and `second` were both signed with `secret`
+pub fn prove_linking(secret: &Scalar, first: &Lsag, second: &Lsag) -> Result<LinkingProof, String> {
+    let ring = common_members(first, second);
+    let public_key = Secp256k1::mul_generator(secret);
+    let signer_index = ring
+        .iter()
+        .position(|point| *point == public_key)
+        .ok_or_else(|| "The public key is not a member of both rings".to_string())?;
+
+    let mapped = [first, second].map(|signature| {
+        compute_mapped_point::<Secp256k1>(&public_key, &signature.linkability_flag)
+    });
+    if Secp256k1::mul(&mapped[0], secret) != first.key_image
+        || Secp256k1::mul(&mapped[1], secret) != second.key_image
+    {
+        return Err("The signatures were not signed with this key".to_string());
+    }
+
+    let prefix = hash_prefix(&ring, first, second);
+    let alpha = Secp256k1::random_scalar();
+
+    // generate random responses for every public key in the ring
+    let mut responses: Vec<Scalar> = ring.iter().map(|_| Secp256k1::random_scalar()).collect();
+
+    // contains all the cees from 0 to ring.len() - 1 (0, 1, ..., pi, ..., ring.len() - 1)
+    let mut cees = vec![Scalar::ZERO; ring.len()];
+
+    // compute cpi+1
+    cees[(signer_index + 1) % ring.len()] = compute_c_from_points(
+        &prefix,
+        &[
+            Secp256k1::mul_generator(&alpha),
+            Secp256k1::mul(&mapped[0], &alpha),
+            Secp256k1::mul(&mapped[1], &alpha),
+        ],
+    );
+
+    // compute the c values from pi+2 to pi (included)
+    for i in signer_index + 2..signer_index + ring.len() + 1 {
+        let index = i % ring.len();
+        let previous_index = (index + ring.len() - 1) % ring.len();
+
+        cees[index] = compute_c(
+            &prefix,
+            &ring[previous_index],
+            &responses[previous_index],
+            &cees[previous_index],
+            first,
+            second,
+        );
+    }
+
+    // compute the signer response
+    responses[signer_index] = alpha - cees[signer_index] * secret;
+
+    Ok(LinkingProof {
+        c0: cees[0],
+        responses,
+    })
+}
+
+/// Verifies that `first` and `second` are valid signatures, signed with the same key.
+/// Returns `true` if the proof is valid, `false` otherwise.
+pub fn verify_linking(proof: &LinkingProof, first: &Lsag, second: &Lsag) -> bool {
+    for signature in [first, second] {
+        if !verify_lsag(signature.clone()) {
+            return false;
+        }
+    }
+
+    let ring = common_members(first, second);
+    // the proof has one response per member of both rings
+    if ring.is_empty() || ring.len() != proof.responses.len() {
+        return false;
+    }
+    let prefix = hash_prefix(&ring, first, second);
+
+    // Compute the c values: c1', c2', ..., cn', c0'
+    let mut last_computed_c = proof.c0;
+    for (public_key, response) in ring.iter().zip(&proof.responses) {
+        last_computed_c = compute_c(
+            &prefix,
+            public_key,
+            response,
+            &last_computed_c,
+            first,
+            second,
+        );
+    }
+
+    // Return true if c0 == c0'
+    proof.c0 == last_computed_c
+}
+
+/// Returns the members of the first signature ring that are in the second one
+fn common_members(first: &Lsag, second: &Lsag) -> Vec<AffinePoint> {
+    first
+        .ring
+        .iter()
+        .filter(|point| second.ring.contains(point))
+        .copied()
+        .collect()
+}
+
+/// Serializes the ring, the key images and the flags and messages of both signatures
+fn hash_prefix(ring: &[AffinePoint], first: &Lsag, second: &Lsag) -> String {
+    let mut prefix = PROOF_DOMAIN.to_string();
+    prefix += &ring
+        .iter()
+        .map(Secp256k1::serialize_point)
+        .collect::<String>();
+    for signature in [first, second] {
+        prefix += &Secp256k1::serialize_point(&signature.key_image);
+        prefix += signature.linkability_flag.as_deref().unwrap_or("");
+        prefix += &signature.message;
+    }
+    prefix
+}
+
+/// Computes the 'cee' value following `public_key`, from `r*G + c*K`, `r*H(K, flag A) + c*I_A`
+/// and `r*H(K, flag B) + c*I_B`
+fn compute_c(
+    prefix: &str,
+    public_key: &AffinePoint,
+    previous_r: &Scalar,
+    previous_c: &Scalar,
+    first: &Lsag,
+    second: &Lsag,
+) -> Scalar {
+    let [point_a, point_b] = [first, second].map(|signature| {
+        let mapped = compute_mapped_point::<Secp256k1>(public_key, &signature.linkability_flag);
+        Secp256k1::lincomb(&mapped, previous_r, &signature.key_image, previous_c)
+    });
+
+    compute_c_from_points(
+        prefix,
+        &[
+            Secp256k1::lincomb(&Secp256k1::generator(), previous_r, public_key, previous_c),
+            point_a,
+            point_b,
+        ],
+    )
+}
+
+fn compute_c_from_points(prefix: &str, points: &[AffinePoint; 3]) -> Scalar {
+    let hash_content = prefix.to_string()
+        + &points
+            .iter()
+            .map(Secp256k1::serialize_point)
+            .collect::<String>();
+
+    Secp256k1::scalar_from_hash(&Keccak256::digest(hash_content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_linking() {
+        let secret = Scalar::from(4u64);
+        let poll_a = sign_lsag(
+            &get_sorted_ring(&[2, 3, 4, 5]),
+            secret,
+            "yes".to_string(),
+            Some("poll A".to_string()),
+        )
+        .unwrap();
+        let poll_b = sign_lsag(
+            &get_sorted_ring(&[3, 4, 6]),
+            secret,
+            "no".to_string(),
+            Some("poll B".to_string()),
+        )
+        .unwrap();
+        assert_ne!(poll_a.key_image, poll_b.key_image);
+
+        let proof = prove_linking(&secret, &poll_a, &poll_b).expect("failed to prove");
+        // the proof ring is made of the members of both rings
+        assert_eq!(proof.responses.len(), 2);
+        assert!(verify_linking(&proof, &poll_a, &poll_b));
+        assert_eq!(LinkingProof::from_json(&proof.to_json()), Ok(proof.clone()));
+
+        // the proof is bound to the signatures
+        assert!(!verify_linking(&proof, &poll_b, &poll_a));
+        let other_b = sign_lsag(
+            &get_sorted_ring(&[3, 4, 6]),
+            Scalar::from(3u64),
+            "no".to_string(),
+            Some("poll B".to_string()),
+        )
+        .unwrap();
+        assert!(!verify_linking(&proof, &poll_a, &other_b));
+
+        // signatures of different keys cannot be linked
+        assert!(prove_linking(&secret, &poll_a, &other_b).is_err());
+        assert!(prove_linking(&Scalar::from(2u64), &poll_a, &poll_b).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::lsag_verifier::{
+    compute_c, compute_c_from_points, compute_mapped_point, GenericLsag, Lsag, Params,
+};
+use crate::ring::{GenericRing, Ring};
+use crate::utils::curve::RingCurve;
+use crate::utils::keccak256::keccak_256;
+use elliptic_curve::Field;
+use k256::Secp256k1;
+
+/// Signs a message using a LSAG ring signature, as `RingSignature.sign()` does in `lsag-ts`.
+///
+/// The ring must be sorted by x ascending coordinate (and y ascending if x's are equal).
+/// If the ring does not contain the signer public key, it is inserted at its sorted position.
+pub fn sign_lsag(
+    ring: &[k256::AffinePoint],
+    signer_private_key: k256::Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Lsag, String> {
+    sign_generic_lsag::<Secp256k1>(ring, signer_private_key, message, linkability_flag)
+}
+
+/// Signs a message using a LSAG ring signature on any supported curve (see `sign_lsag`)
+pub fn sign_generic_lsag<C: RingCurve>(
+    ring: &[C::Point],
+    signer_private_key: C::Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<GenericLsag<C>, String> {
+    if bool::from(signer_private_key.is_zero()) {
+        return Err("Signer private key cannot be 0".to_string());
+    }
+
+    let (ring, signer_index) = insert_generic_signer::<C>(ring, &signer_private_key)?;
+    let signer_public_key = ring[signer_index];
+
+    let message_digest = keccak_256(std::slice::from_ref(&message));
+    let serialized_ring = ring.serialized().to_string();
+
+    let mapped = compute_mapped_point::<C>(&signer_public_key, &linkability_flag);
+    let key_image = C::mul(&mapped, &signer_private_key);
+
+    let alpha = C::random_scalar();
+
+    // generate random responses for every public key in the ring
+    let mut responses: Vec<C::Scalar> = ring.iter().map(|_| C::random_scalar()).collect();
+
+    // contains all the cees from 0 to ring.len() - 1 (0, 1, ..., pi, ..., ring.len() - 1)
+    let mut cees = vec![C::Scalar::ZERO; ring.len()];
+
+    // compute cpi+1
+    cees[(signer_index + 1) % ring.len()] = compute_c_from_points::<C>(
+        &serialized_ring,
+        &message_digest,
+        C::mul_generator(&alpha),
+        C::mul(&mapped, &alpha),
+    );
+
+    // compute the c values from pi+2 to pi (included)
+    for i in signer_index + 2..signer_index + ring.len() + 1 {
+        let index = i % ring.len();
+        let previous_index = (index + ring.len() - 1) % ring.len();
+
+        let params = Params::<C> {
+            index,
+            previous_r: responses[previous_index],
+            previous_c: cees[previous_index],
+            previous_index,
+            linkability_flag: linkability_flag.clone(),
+            key_image,
+        };
+
+        cees[index] = compute_c(
+            &ring,
+            serialized_ring.clone(),
+            message_digest.clone(),
+            &params,
+        );
+    }
+
+    // compute the signer response
+    responses[signer_index] = alpha - cees[signer_index] * signer_private_key;
+
+    Ok(GenericLsag {
+        ring: ring.into_points(),
+        message,
+        c0: cees[0],
+        responses,
+        key_image,
+        linkability_flag,
+        config: None,
+        evm_witnesses: None,
+    })
+}
+
+/// Checks that the ring is in canonical order and inserts the signer public key at its sorted position if needed.
+/// Returns the resulting ring and the signer index.
+pub fn insert_signer(
+    ring: &[k256::AffinePoint],
+    signer_private_key: &k256::Scalar,
+) -> Result<(Ring, usize), String> {
+    insert_generic_signer::<Secp256k1>(ring, signer_private_key)
+}
+
+/// Inserts the signer public key in a ring on any supported curve (see `insert_signer`)
+pub fn insert_generic_signer<C: RingCurve>(
+    ring: &[C::Point],
+    signer_private_key: &C::Scalar,
+) -> Result<(GenericRing<C>, usize), String> {
+    let signer_public_key = C::mul_generator(signer_private_key);
+
+    if ring.is_empty() {
+        return Ok((GenericRing::from_sorted(vec![signer_public_key])?, 0));
+    }
+
+    GenericRing::from_sorted(ring.to_vec())?.with_member(signer_public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign_generic_lsag, sign_lsag};
+    use crate::lsag_verifier::{verify_lsag, Lsag, P256Lsag, RistrettoLsag};
+    use crate::ring::GenericRing;
+    use crate::utils::curve::{RingCurve, Ristretto255};
+    use crate::utils::test_utils::get_sorted_ring;
+    use k256::Scalar;
+    use p256::NistP256;
+
+    #[test]
+    fn test_sign_lsag() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let signer_private_key = Scalar::from(42u64);
+
+        let signature = sign_lsag(
+            &ring,
+            signer_private_key,
+            "message".to_string(),
+            Some("linkability flag".to_string()),
+        )
+        .expect("failed to sign");
+
+        assert_eq!(signature.ring.len(), 5);
+        assert!(verify_lsag(signature.clone()));
+
+        // the signature can be forwarded in the lsag-ts format
+        assert!(verify_lsag(
+            crate::lsag_verifier::Lsag::from_base64(&signature.to_base64()).unwrap()
+        ));
+
+        let mut tampered = signature;
+        tampered.message = "another message".to_string();
+        assert!(!verify_lsag(tampered));
+    }
+
+    #[test]
+    fn test_key_image_is_linkable() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let signer_private_key = Scalar::from(3u64);
+        let flag = Some("flag".to_string());
+
+        let first = sign_lsag(&ring, signer_private_key, "a".to_string(), flag.clone()).unwrap();
+        let second = sign_lsag(&ring, signer_private_key, "b".to_string(), flag).unwrap();
+        let other_flag = sign_lsag(&ring, signer_private_key, "b".to_string(), None).unwrap();
+
+        // the signer is already in the ring
+        assert_eq!(first.ring, ring.points());
+        assert_eq!(first.key_image, second.key_image);
+        assert_ne!(first.key_image, other_flag.key_image);
+        assert!(verify_lsag(other_flag));
+    }
+
+    #[test]
+    fn test_sign_lsag_errors() {
+        let mut ring = get_sorted_ring(&[2, 3, 4]).into_points();
+        ring.reverse();
+        assert!(sign_lsag(&ring, Scalar::from(7u64), "message".to_string(), None).is_err());
+        assert!(sign_lsag(&[], Scalar::ZERO, "message".to_string(), None).is_err());
+
+        // a ring of one is a schnorr signature
+        let signature = sign_lsag(&[], Scalar::from(7u64), "message".to_string(), None).unwrap();
+        assert!(verify_lsag(signature));
+    }
+
+    #[test]
+    fn test_sign_p256_lsag() {
+        let ring = GenericRing::<NistP256>::new(
+            [2u64, 3, 4, 5]
+                .iter()
+                .map(|k| (p256::AffinePoint::GENERATOR * p256::Scalar::from(*k)).to_affine())
+                .collect(),
+        )
+        .unwrap();
+        let signer_private_key = p256::Scalar::from(4u64);
+        let flag = Some("passkeys".to_string());
+
+        let signature = sign_generic_lsag::<NistP256>(
+            &ring,
+            signer_private_key,
+            "message".to_string(),
+            flag.clone(),
+        )
+        .expect("failed to sign");
+        let other =
+            sign_generic_lsag::<NistP256>(&ring, signer_private_key, "other".to_string(), flag)
+                .expect("failed to sign");
+
+        assert_eq!(signature.ring, ring.points());
+        assert_eq!(signature.key_image, other.key_image);
+        assert!(verify_lsag(signature.clone()));
+
+        let json = signature.to_json();
+        assert!(json.contains(r#""curve":"{\"curve\":\"P256\"}""#));
+        assert_eq!(P256Lsag::from_json(&json), Ok(signature.clone()));
+        assert!(Lsag::from_json(&json).is_err());
+
+        let mut tampered = signature;
+        tampered.message = "another message".to_string();
+        assert!(!verify_lsag(tampered));
+    }
+
+    #[test]
+    fn test_sign_ristretto255_lsag() {
+        let ring = GenericRing::<Ristretto255>::new(
+            [2u64, 3, 4, 5]
+                .iter()
+                .map(|k| Ristretto255::mul_generator(&curve25519_dalek::Scalar::from(*k)))
+                .collect(),
+        )
+        .unwrap();
+        let signer_private_key = curve25519_dalek::Scalar::from(42u64);
+        let flag = Some("linkability flag".to_string());
+
+        let signature = sign_generic_lsag::<Ristretto255>(
+            &ring,
+            signer_private_key,
+            "message".to_string(),
+            flag.clone(),
+        )
+        .expect("failed to sign");
+        let other =
+            sign_generic_lsag::<Ristretto255>(&ring, signer_private_key, "other".to_string(), flag)
+                .expect("failed to sign");
+
+        assert_eq!(signature.ring.len(), 5);
+        assert_eq!(signature.key_image, other.key_image);
+        assert!(verify_lsag(signature.clone()));
+
+        let json = signature.to_json();
+        assert!(json.contains(r#""curve":"{\"curve\":\"RISTRETTO255\"}""#));
+        assert_eq!(RistrettoLsag::from_json(&json), Ok(signature.clone()));
+        assert!(P256Lsag::from_json(&json).is_err());
+
+        let mut tampered = signature;
+        tampered.responses[0] += curve25519_dalek::Scalar::ONE;
+        assert!(!verify_lsag(tampered));
+    }
+}
@@ -1,138 +1,250 @@
+use crate::ring::Ring;
+use crate::utils::base64_json::decode_base64_json;
+use crate::utils::curve::{Curve, RingCurve, Ristretto255};
+use crate::utils::hex_to_decimal::hex_to_decimal;
 use crate::utils::keccak256::keccak_256;
-use crate::utils::scalar_from_hex::scalar_from_hex;
-use crate::utils::serialize_point::{deserialize_point, serialize_point};
-use crate::utils::serialize_ring::{deserialize_ring, serialize_ring};
-use crate::utils::{hash_to_secp256k1::hash_to_secp256k1, hex_to_decimal::hex_to_decimal};
+use crate::utils::linkability_flag::parse_linkability_flag;
+use crate::utils::signature_config::{HashFunction, SignatureConfig};
 use base64::engine::general_purpose;
 use base64::Engine;
-use core::str;
-use k256::{AffinePoint, Scalar};
-use serde::Deserialize;
+use k256::Secp256k1;
+use p256::NistP256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 
 /// Define a struct that matches the structure of the JSON string LSAG
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct StringifiedLsag {
     pub message: String,
     pub ring: Vec<String>,
     pub c: String,
     pub responses: Vec<String>,
+    pub curve: String,
     pub keyImage: String,
     pub linkabilityFlag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<SignatureConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evmWitnesses: Option<Vec<String>>,
 }
-/// A struct to represent a LSAG signature
-pub struct Lsag {
-    pub ring: Vec<AffinePoint>,
+/// A struct to represent a LSAG signature on any supported curve
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericLsag<C: RingCurve> {
+    pub ring: Vec<C::Point>,
     pub message: String,
-    pub c0: Scalar,
-    pub responses: Vec<Scalar>,
-    pub key_image: AffinePoint,
+    pub c0: C::Scalar,
+    pub responses: Vec<C::Scalar>,
+    pub key_image: C::Point,
     pub linkability_flag: Option<String>,
+    pub config: Option<SignatureConfig>,
+    pub evm_witnesses: Option<Vec<String>>,
 }
+
+/// A LSAG signature on secp256k1, as produced by `lsag-ts`
+pub type Lsag = GenericLsag<Secp256k1>;
+
+/// A LSAG signature on NIST P-256, e.g. with a ring of passkeys
+pub type P256Lsag = GenericLsag<NistP256>;
+
+/// A LSAG signature on ristretto255
+pub type RistrettoLsag = GenericLsag<Ristretto255>;
+
+impl<C: RingCurve> GenericLsag<C> {
+    /// Parses a LSAG signature from the JSON string produced by `toJsonString()` in `lsag-ts`
+    pub fn from_json(json: &str) -> Result<GenericLsag<C>, String> {
+        let stringified: StringifiedLsag =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        GenericLsag::try_from(stringified)
+    }
+
+    /// Converts the signature to a JSON string, byte-identical to `toJsonString()` in `lsag-ts`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedLsag::from(self)).unwrap()
+    }
+
+    /// Parses a LSAG signature from the base64 string produced by `toBase64()` in `lsag-ts`
+    pub fn from_base64(b64_signature: &str) -> Result<GenericLsag<C>, String> {
+        GenericLsag::from_json(&decode_base64_json(b64_signature)?)
+    }
+
+    /// Converts the signature to a base64 string, byte-identical to `toBase64()` in `lsag-ts`
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.to_json())
+    }
+}
+
+impl<C: RingCurve> TryFrom<StringifiedLsag> for GenericLsag<C> {
+    type Error = String;
+
+    fn try_from(json: StringifiedLsag) -> Result<GenericLsag<C>, String> {
+        if json.ring.is_empty() {
+            return Err("Ring cannot be empty".to_string());
+        }
+        if json.ring.len() != json.responses.len() {
+            return Err("Ring and responses must have the same length".to_string());
+        }
+
+        let curve = Curve::from_json_string(&json.curve)?;
+        if curve != C::CURVE {
+            return Err(format!(
+                "Expected a {} signature, got a {} signature",
+                C::CURVE.name(),
+                curve.name()
+            ));
+        }
+
+        let ring = json
+            .ring
+            .iter()
+            .map(|point| C::deserialize_point(point))
+            .collect::<Result<Vec<C::Point>, String>>()?;
+        let key_image = C::deserialize_point(&json.keyImage)?;
+        let c0 = C::scalar_from_hex(&json.c)?;
+        let responses = json
+            .responses
+            .iter()
+            .map(|response| C::scalar_from_hex(response))
+            .collect::<Result<Vec<C::Scalar>, String>>()?;
+
+        Ok(GenericLsag {
+            ring,
+            message: json.message,
+            c0,
+            responses,
+            key_image,
+            linkability_flag: parse_linkability_flag(json.linkabilityFlag),
+            config: json.config,
+            evm_witnesses: json.evmWitnesses,
+        })
+    }
+}
+
+impl<C: RingCurve> From<&GenericLsag<C>> for StringifiedLsag {
+    fn from(signature: &GenericLsag<C>) -> StringifiedLsag {
+        StringifiedLsag {
+            message: signature.message.clone(),
+            ring: signature.ring.iter().map(C::serialize_point).collect(),
+            c: C::scalar_to_hex(&signature.c0),
+            responses: signature.responses.iter().map(C::scalar_to_hex).collect(),
+            curve: C::CURVE.to_json_string(),
+            keyImage: C::serialize_point(&signature.key_image),
+            linkabilityFlag: signature.linkability_flag.clone().unwrap_or_default(),
+            config: signature.config.clone(),
+            evmWitnesses: signature.evm_witnesses.clone(),
+        }
+    }
+}
+
+/// Checks that the signature config is supported by `verify_lsag`:
+/// only keccak256 hashing without EVM compatibility is implemented for now
+fn check_config(config: &Option<SignatureConfig>) -> Result<(), String> {
+    let Some(config) = config else {
+        return Ok(());
+    };
+    if let Some(hash @ (HashFunction::Sha256 | HashFunction::Sha512)) = config.hash {
+        return Err(format!("Unsupported hash function: {}", hash.name()));
+    }
+    if config.evm_compatibility == Some(true) {
+        return Err("EVM compatibility is not supported for LSAG signatures".to_string());
+    }
+
+    Ok(())
+}
+
 /// Parameters required for the compute_c function
-pub struct Params {
+pub struct Params<C: RingCurve> {
     pub index: usize,
-    pub previous_r: Scalar,
-    pub previous_c: Scalar,
+    pub previous_r: C::Scalar,
+    pub previous_c: C::Scalar,
     pub previous_index: usize,
     pub linkability_flag: Option<String>,
-    pub key_image: AffinePoint,
+    pub key_image: C::Point,
 }
 
 /// Computes the 'cee' value based on the provided parameters
-pub fn compute_c(
-    ring: &[AffinePoint], // todo: ensure ring is sorted
+pub fn compute_c<C: RingCurve>(
+    ring: &[C::Point],
     serialized_ring: String,
     message_digest: String,
-    params: &Params,
-    // curve_order: Scalar,
-) -> Scalar {
-    let g = AffinePoint::GENERATOR;
-
-    let point =
-        ((g * params.previous_r) + (ring[params.previous_index] * params.previous_c)).to_affine();
-
-    let mapped = hash_to_secp256k1(
-        serialize_point(ring[params.previous_index])
-            + &params.linkability_flag.clone().unwrap_or("".to_string()),
+    params: &Params<C>,
+) -> C::Scalar {
+    let previous_public_key = &ring[params.previous_index];
+
+    let point = C::lincomb(
+        &C::generator(),
+        &params.previous_r,
+        previous_public_key,
+        &params.previous_c,
     );
 
+    let mapped = compute_mapped_point::<C>(previous_public_key, &params.linkability_flag);
+
+    compute_c_from_points::<C>(
+        &serialized_ring,
+        &message_digest,
+        point,
+        C::lincomb(
+            &mapped,
+            &params.previous_r,
+            &params.key_image,
+            &params.previous_c,
+        ),
+    )
+}
+
+/// Computes the 'cee' value from the two points of a ring iteration:
+/// `r*G + c*K` and `r*H(K, flag) + c*I` (or `alpha*G` and `alpha*H(K, flag)` for the signer)
+pub fn compute_c_from_points<C: RingCurve>(
+    serialized_ring: &str,
+    message_digest: &str,
+    point: C::Point,
+    mapped_point: C::Point,
+) -> C::Scalar {
     let hash_content = "".to_string()
-        + &serialized_ring
-        + &hex_to_decimal(&message_digest).unwrap()
-        + &serialize_point(point)
-        + &serialize_point(
-            ((mapped * params.previous_r) + (params.key_image * params.previous_c)).to_affine(),
-        );
+        + serialized_ring
+        + &hex_to_decimal(message_digest).unwrap()
+        + &C::serialize_point(&point)
+        + &C::serialize_point(&mapped_point);
 
-    let hash = keccak_256(&[hash_content]);
+    let hash = Keccak256::digest(hash_content.as_bytes());
 
-    scalar_from_hex(&hash).unwrap() // todo: compute mod order: % curve_order;
+    C::scalar_from_hash(&hash)
 }
 
-// Function to convert a JSON string into a Rust struct
-fn convert_string_to_json(json_str: &str) -> StringifiedLsag {
-    // Deserialize the JSON string into the Rust struct
-    serde_json::from_str(json_str).unwrap()
+/// Maps a public key and the linkability flag to a point on the curve.
+/// The key image of a signer is this point multiplied by the signer private key.
+pub fn compute_mapped_point<C: RingCurve>(
+    public_key: &C::Point,
+    linkability_flag: &Option<String>,
+) -> C::Point {
+    C::hash_to_curve(C::serialize_point(public_key) + linkability_flag.as_deref().unwrap_or(""))
 }
 
 /// Verify a base64 encoded LSAG signature.
 /// Converts a base64 encoded LSAG signature and verifies it.
 pub fn verify_b64_lsag(b64_signature: String) -> bool {
-    // Decode the base64 string
-    let decoded_bytes = general_purpose::STANDARD
-        .decode(b64_signature.as_bytes())
-        .unwrap();
-
-    // Convert the byte array to utf8 string
-    let decoded_string = match str::from_utf8(&decoded_bytes) {
-        Ok(ascii) => ascii,
-        Err(_) => panic!("Failed to convert decoded bytes to ASCII string"),
-    };
-
-    // Convert the string to json
-    let json = convert_string_to_json(decoded_string); // Assume the conversion returns a Result
-
-    // Deserialize the ring (handle Result)
-    let ring_points = match deserialize_ring(&json.ring) {
-        Ok(points) => points,
-        Err(e) => {
-            println!("Error deserializing ring: {}", e);
-            return false; // Return false if deserialization fails
-        }
-    };
-
-    let key_image = match deserialize_point(json.keyImage.clone()) {
-        Ok(point) => point,
-        Err(e) => {
-            println!("Error deserializing keyImage: {}", e);
-            return false; // Return false if deserialization fails
-        }
-    };
-
-    let responses: Vec<Scalar> = json
-        .responses
-        .iter()
-        .map(|response| scalar_from_hex(response).unwrap())
-        .collect();
-
-    // return the result of the verification
-    let lsag_signature = Lsag {
-        ring: ring_points,
-        message: json.message.clone(),
-        c0: scalar_from_hex(&json.c).unwrap(),
-        responses,
-        key_image,
-        linkability_flag: Some(json.linkabilityFlag.clone()),
+    let lsag_signature = match Lsag::from_base64(&b64_signature) {
+        Ok(signature) => signature,
+        Err(_) => return false, // Return false if deserialization fails
     };
 
     // Return the result of the verification
     verify_lsag(lsag_signature)
 }
 
+/// Verifies a ring signature whose ring must be in canonical order (see `Ring`).
+/// Returns `false` if the ring is not canonical, even if the signature itself is valid.
+pub fn verify_lsag_canonical(signature: Lsag) -> bool {
+    Ring::is_canonical(&signature.ring) && verify_lsag(signature)
+}
+
 /// Verifies a ring signature.
-/// Returns `true` if the signature is valid, `false` otherwise.
-pub fn verify_lsag(signature: Lsag) -> bool {
+/// Returns `true` if the signature is valid, `false` otherwise, including when its ring is
+/// empty or does not match its responses, or when its config requires a hash function other
+/// than keccak256.
+pub fn verify_lsag<C: RingCurve>(signature: GenericLsag<C>) -> bool {
     // // Check that all points in the ring are valid // todo: implement for rust
     // for point in ring {
     //     if !check_low_order(point) { // todo: add the check_low_order function
@@ -140,13 +252,16 @@ pub fn verify_lsag(signature: Lsag) -> bool {
     //     }
     // }
 
-    // Ensure that the ring and responses have matching lengths
-    if signature.ring.len() != signature.responses.len() {
-        panic!("Ring and responses must have the same length");
+    // Ensure that the ring is not empty and matches the responses
+    if signature.ring.is_empty() || signature.ring.len() != signature.responses.len() {
+        return false;
+    }
+    if check_config(&signature.config).is_err() {
+        return false;
     }
     let message_digest = keccak_256(&[signature.message]);
 
-    let serialized_ring = serialize_ring(&signature.ring);
+    let serialized_ring: String = signature.ring.iter().map(C::serialize_point).collect();
 
     // Initialize last_computed_c with c0
     let mut last_computed_c = signature.c0;
@@ -158,7 +273,7 @@ pub fn verify_lsag(signature: Lsag) -> bool {
         .enumerate()
         .take(signature.ring.len())
     {
-        let params = Params {
+        let params = Params::<C> {
             index: (i.0 + 1) % signature.ring.len(),
             previous_r: signature.responses[i.0],
             previous_c: last_computed_c,
@@ -182,16 +297,18 @@ pub fn verify_lsag(signature: Lsag) -> bool {
 }
 #[cfg(test)]
 mod tests {
-    use super::verify_lsag;
+    use super::{check_config, verify_lsag};
     use crate::{
-        lsag_verifier::{compute_c, Lsag, Params},
+        lsag_verifier::{compute_c, verify_b64_lsag, Lsag, Params, RistrettoLsag},
         utils::{
-            scalar_from_hex::scalar_from_hex, scalar_to_string::scalar_to_string,
-            serialize_point::deserialize_point, test_utils::get_ring,
+            scalar_from_hex::scalar_from_hex,
+            scalar_to_string::scalar_to_string,
+            signature_config::{HashFunction, SignatureConfig},
+            test_utils::get_ring,
         },
     };
     use elliptic_curve::{sec1::FromEncodedPoint, PrimeField};
-    use k256::{AffinePoint, EncodedPoint, Scalar};
+    use k256::{AffinePoint, EncodedPoint, Scalar, Secp256k1};
 
     #[test]
     fn test_compute_c() {
@@ -212,7 +329,7 @@ mod tests {
 
         let key_image = ring[0];
 
-        let params = Params {
+        let params = Params::<Secp256k1> {
             index: 2,
             previous_r: Scalar::from_u128(123),
             previous_c: Scalar::from_u128(456),
@@ -282,6 +399,8 @@ mod tests {
             .to_vec(),
             key_image: key_image.unwrap(),
             linkability_flag: Some("linkability flag".to_string()),
+            config: None,
+            evm_witnesses: None,
         };
         let result = verify_lsag(lsag_signature);
 
@@ -303,69 +422,102 @@ mod tests {
         // Extract the "valid" signature data from the JSON
         let signature_data = &json_data["valid"];
 
-        // Deserialize the message
-        let message = signature_data["message"]
-            .as_str()
-            .expect("message should be a string")
-            .to_string();
+        let lsag_signature =
+            Lsag::from_json(&signature_data.to_string()).expect("failed to parse the signature");
+        let result = verify_lsag(lsag_signature);
 
-        // Deserialize the ring (points)
-        let ring: Vec<AffinePoint> = signature_data["ring"]
-            .as_array()
-            .expect("ring should be an array")
-            .iter()
-            .map(|s| {
-                deserialize_point(
-                    s.as_str()
-                        .expect("ring entry should be a string")
-                        .to_string(),
-                )
-                .expect("failed to deserialize ring point")
-            })
-            .collect();
-
-        // Deserialize the key image
-        let key_image_str = signature_data["keyImage"]
-            .as_str()
-            .expect("keyImage should be a string")
-            .to_string();
-        let key_image = deserialize_point(key_image_str).expect("failed to deserialize key image");
-
-        // Deserialize the challenge scalar `c`
-        let c = scalar_from_hex(signature_data["c"].as_str().expect("c should be a string"))
-            .expect("failed to parse c");
-
-        // Deserialize the response scalars
-        let responses: Vec<Scalar> = signature_data["responses"]
-            .as_array()
-            .expect("responses should be an array")
-            .iter()
-            .map(|r| {
-                scalar_from_hex(r.as_str().expect("response should be a string"))
-                    .expect("failed to parse response scalar")
+        // Verify that the LSAG signature is valid
+        assert!(result, "The LSAG signature should be valid.");
+    }
+
+    /// Base64 signature produced by `toBase64()` in `lsag-ts`
+    const TS_B64_SIGNATURE: &str = "eyJtZXNzYWdlIjoibWVzc2FnZSIsInJpbmciOlsiMDIwOGY0ZjM3ZTJkOGY3NGUxOGMxYjhmZGUyMzc0ZDVmMjg0MDJmYjhhYjdmZDFjYzViNzg2YWE0MDg1MWE3MGNiIiwiMDMxNmQ3ZGE3MGJhMjQ3YTZhNDBiYjMxMDE4N2U4Nzg5YjgwYzQ1ZmE2ZGMwMDYxYWJiOGNlZDQ5Y2JlN2Y4ODdmIiwiMDIyMTg2OWNhM2FlMzNiZTNhNzMyN2U5YTAyNzIyMDNhZmE3MmM1MmE1NDYwY2ViOWY0YTUwOTMwNTMxYmQ5MjZhIiwiMDIzMzdkNmY1NzdlNjZhMjFhNzgzMWMwODdjNjgzNmExYmFlMzcwODZiZjQzMTQwMDgxMWFjN2M2ZTk2YzhjY2JiIl0sImMiOiI4NjM3OWI0Mzg2MWU5NTBiNWZhNGI3NTcxYWZmMGM2MDA0NTc4ZTcxMjgwYWFlZGI5OTM4MzNjOWJkZTYzYzQzIiwicmVzcG9uc2VzIjpbImQ2YzE4NTRlZWIxMzJkNTg4NmFjNTkwYzUzMGE1NWE3ZmJhM2Q5MmM0ZWI2ODk2YTcyOGIwYTYxODk5YWQ5MDIiLCI2YTUxZDczMWIzOTgwMzZlZDNiM2I1Y2ZkMjA2NDA3YTM1ZmQxMWZhYTJiYmFkMTY1OGJjZjlmMDhiOWM1ZmI4IiwiNmE1MWQ3MzFiMzk4MDM2ZWQzYjNiNWNmZDIwNjQwN2EzNWZkMTFmYWEyYmJhZDE2NThiY2Y5ZjA4YjljNWZiOCIsIjZhNTFkNzMxYjM5ODAzNmVkM2IzYjVjZmQyMDY0MDdhMzVmZDExZmFhMmJiYWQxNjU4YmNmOWYwOGI5YzVmYjgiXSwiY3VydmUiOiJ7XCJjdXJ2ZVwiOlwiU0VDUDI1NksxXCJ9Iiwia2V5SW1hZ2UiOiIwMjE5MWViOWYwNjM2YTViMWE4N2VkNjZjYzAwZDViM2ZmYTM1ZDRlMDRjNGIyMWM4ZTQ4ZGI5ODdhYmI2MDBiMTEiLCJsaW5rYWJpbGl0eUZsYWciOiJsaW5rYWJpbGl0eSBmbGFnIiwiZXZtV2l0bmVzc2VzIjpbXX0=";
+
+    #[test]
+    fn test_verify_b64_lsag() {
+        assert!(verify_b64_lsag(TS_B64_SIGNATURE.to_string()));
+        assert!(!verify_b64_lsag("not a base64 signature".to_string()));
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        let signature = Lsag::from_base64(TS_B64_SIGNATURE).expect("failed to parse signature");
+
+        assert_eq!(signature.evm_witnesses, Some(vec![]));
+        assert_eq!(signature.to_base64(), TS_B64_SIGNATURE);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        // `c` and the responses are not zero padded by `toJsonString()`
+        let json = r#"{"message":"Hello World","ring":["0208f4f37e2d8f74e18c1b8fde2374d5f28402fb8ab7fd1cc5b786aa40851a70cb","0316d7da70ba247a6a40bb310187e8789b80c45fa6dc0061abb8ced49cbe7f887f"],"c":"9b43861e950b5fa4b7571aff0c6004578e71280aaedb993833c9bde63c43","responses":["abc","6a51d731b398036ed3b3b5cfd206407a35fd11faa2bbad1658bcf9f08b9c5fb8"],"curve":"{\"curve\":\"SECP256K1\"}","keyImage":"02191eb9f0636a5b1a87ed66cc00d5b3ffa35d4e04c4b21c8e48db987abb600b11","linkabilityFlag":"flag","config":{"evmCompatibility":false,"hash":"keccak256"}}"#;
+
+        let signature = Lsag::from_json(json).expect("failed to parse signature");
+
+        assert_eq!(
+            signature.config,
+            Some(SignatureConfig {
+                evm_compatibility: Some(false),
+                hash: Some(HashFunction::Keccak256),
             })
-            .collect();
+        );
+        assert_eq!(signature.evm_witnesses, None);
+        assert_eq!(signature.to_json(), json);
+    }
 
-        // Get the linkability flag
-        let linkability_flag = signature_data["linkabilityFlag"]
-            .as_str()
-            .expect("linkabilityFlag should be a string")
-            .to_string();
+    #[test]
+    fn test_from_json_errors() {
+        let valid = Lsag::from_base64(TS_B64_SIGNATURE).unwrap();
 
-        // Call `verify_lsag` and check the result
-        let lsag_signature = Lsag {
-            ring,
-            message,
-            c0: c,
-            responses,
-            key_image,
-            linkability_flag: Some(linkability_flag),
-        };
-        let result = verify_lsag(lsag_signature);
+        let mut unknown_curve = valid.to_json();
+        unknown_curve = unknown_curve.replace("SECP256K1", "SECP384R1");
+        assert!(Lsag::from_json(&unknown_curve).is_err());
 
-        // Verify that the LSAG signature is valid
-        assert!(result, "The LSAG signature should be valid.");
+        let mut missing_response = valid.clone();
+        missing_response.responses.pop();
+        assert!(Lsag::from_json(&missing_response.to_json()).is_err());
+
+        assert!(Lsag::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_config() {
+        let valid = Lsag::from_base64(TS_B64_SIGNATURE).unwrap();
+
+        let mut sha512 = valid.clone();
+        sha512.config = Some(SignatureConfig {
+            evm_compatibility: None,
+            hash: Some(HashFunction::Sha512),
+        });
+        assert_eq!(Lsag::from_json(&sha512.to_json()), Ok(sha512.clone()));
+        assert_eq!(
+            check_config(&sha512.config),
+            Err("Unsupported hash function: sha512".to_string())
+        );
+        assert!(!verify_lsag(sha512));
+
+        let mut evm_compatible = valid;
+        evm_compatible.config = Some(SignatureConfig {
+            evm_compatibility: Some(true),
+            hash: Some(HashFunction::Keccak256),
+        });
+        assert!(Lsag::from_json(&evm_compatible.to_json()).is_ok());
+        assert!(!verify_lsag(evm_compatible));
     }
 
-    // todo: test to verify a signature from a base64 string from the lsag package
+    /// Test the `verify_lsag` function with a ristretto255 signature computed by this crate,
+    /// with the private keys 2, 3, 4, 5 and 42 (the signer)
+    #[test]
+    fn test_verify_ristretto255_lsag() {
+        let json = r#"{"message":"message","ring":["6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919","94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259","da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57","e00af9c74d9edb8ebcc160ceec97d531cbd6e2956f9e9162b8e9eda260e82e43","e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e"],"c":"17ebfa06c3a908a9700a854d17410f994ba742e86ec7ca1761d6a02e31067fe","responses":["aa5c63e2659f17e5e4b0bdd283d7d9553f73d96d17ed7070c29b96fa612e070","292a2d520664e4b413f28d92e459d825ea6c9e2c2a683839b19166a78b869d9","f4a0530fab20745f3b62ff38b90922bbd1b3785931e1d35f0251f7f3c90732c","b2441c066ad33bfb727bb2afb8d85f44035846b8f80c9ab643b4602633df1b7","6d116fc25a0eaa904b693629b749ce9b895f76f0e4be988cf15a991107fba60"],"curve":"{\"curve\":\"RISTRETTO255\"}","keyImage":"3efaa30934e8bc7242df7ad305df7c6c811895eb57b6d1e4d5bf2ff815e8a034","linkabilityFlag":"linkability flag"}"#;
+
+        let signature = RistrettoLsag::from_json(json).expect("failed to parse the signature");
+        assert_eq!(signature.to_json(), json);
+        assert!(verify_lsag(signature.clone()));
+        assert!(Lsag::from_json(json).is_err());
+
+        let mut other_flag = signature;
+        other_flag.linkability_flag = None;
+        assert!(!verify_lsag(other_flag));
+    }
 }
@@ -0,0 +1,177 @@
+//! Merkle trees over ring members, compatible with OpenZeppelin's `MerkleProof` library.
+//!
+//! - a leaf is `keccak256(bytes.concat(keccak256(publicKey)))`, where `publicKey` is the
+//!   33-byte compressed public key
+//! - leaves are sorted, so the root only depends on the set of public keys
+//! - pairs are hashed sorted (`keccak256(min(a, b) || max(a, b))`)
+//! - a node without sibling is moved up to the next level as is
+
+use crate::lsag_verifier::{verify_lsag, Lsag};
+use crate::sag_verifier::{verify_sag, Sag};
+use elliptic_curve::sec1::ToEncodedPoint;
+use k256::AffinePoint;
+use sha3::{Digest, Keccak256};
+
+/// A Merkle tree over the members of a ring
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// The levels of the tree, from the sorted leaves to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree of a ring. Returns an error if the ring is empty or contains duplicates.
+    pub fn new(ring: &[AffinePoint]) -> Result<MerkleTree, String> {
+        if ring.is_empty() {
+            return Err("Ring cannot be empty".to_string());
+        }
+
+        let mut leaves: Vec<[u8; 32]> = ring.iter().map(leaf_hash).collect();
+        leaves.sort();
+        if leaves.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("Duplicates points found in the ring".to_string());
+        }
+
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next_level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next_level);
+        }
+
+        Ok(MerkleTree { levels })
+    }
+
+    /// Returns the root of the tree
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Returns the root of the tree as a 0x-prefixed hex string, as `bytes32` are displayed in Solidity
+    pub fn root_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.root()))
+    }
+
+    /// Returns the inclusion proof of a member, from the leaf level to the root.
+    /// Returns `None` if the public key is not a member of the ring.
+    pub fn proof(&self, public_key: &AffinePoint) -> Option<Vec<[u8; 32]>> {
+        let leaf = leaf_hash(public_key);
+        let mut index = self.levels[0].binary_search(&leaf).ok()?;
+
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+/// Verifies the inclusion proof of a member, as `MerkleProof.verify` does in OpenZeppelin
+pub fn verify_proof(root: &[u8; 32], public_key: &AffinePoint, proof: &[[u8; 32]]) -> bool {
+    let computed_root = proof.iter().fold(leaf_hash(public_key), |node, sibling| {
+        hash_pair(&node, sibling)
+    });
+
+    computed_root == *root
+}
+
+/// Returns the root of the Merkle tree of a ring
+pub fn ring_root(ring: &[AffinePoint]) -> Result<[u8; 32], String> {
+    Ok(MerkleTree::new(ring)?.root())
+}
+
+/// Verifies a LSAG signature whose ring must be exactly the set of public keys committed in `root`
+pub fn verify_lsag_with_root(signature: Lsag, root: &[u8; 32]) -> bool {
+    match ring_root(&signature.ring) {
+        Ok(ring_root) if ring_root == *root => verify_lsag(signature),
+        _ => false,
+    }
+}
+
+/// Verifies a SAG signature whose ring must be exactly the set of public keys committed in `root`
+pub fn verify_sag_with_root(signature: Sag, root: &[u8; 32]) -> bool {
+    match ring_root(&signature.ring) {
+        Ok(ring_root) if ring_root == *root => verify_sag(signature),
+        _ => false,
+    }
+}
+
+fn leaf_hash(public_key: &AffinePoint) -> [u8; 32] {
+    let public_key_hash = Keccak256::digest(public_key.to_encoded_point(true).as_bytes());
+    Keccak256::digest(public_key_hash).into()
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut hasher = Keccak256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::ring::Ring;
+    use crate::utils::test_utils::get_points;
+    use k256::Scalar;
+
+    #[test]
+    fn test_merkle_proofs() {
+        // an odd number of leaves to cover promoted nodes
+        let ring = get_points(&[2, 3, 4, 5, 6]);
+        let tree = MerkleTree::new(&ring).expect("failed to build the tree");
+
+        let mut shuffled = ring.clone();
+        shuffled.reverse();
+        assert_eq!(ring_root(&shuffled), Ok(tree.root()));
+        assert_eq!(tree.root_hex().len(), 66);
+
+        for member in &ring {
+            let proof = tree.proof(member).unwrap();
+            assert!(verify_proof(&tree.root(), member, &proof));
+            assert!(!verify_proof(&tree.root(), &get_points(&[42])[0], &proof));
+        }
+        assert_eq!(tree.proof(&get_points(&[42])[0]), None);
+
+        assert!(MerkleTree::new(&[]).is_err());
+        assert!(MerkleTree::new(&get_points(&[2, 2])).is_err());
+    }
+
+    #[test]
+    fn test_single_member_tree() {
+        let ring = get_points(&[2]);
+        let tree = MerkleTree::new(&ring).unwrap();
+
+        assert_eq!(tree.root(), leaf_hash(&ring[0]));
+        assert_eq!(tree.proof(&ring[0]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_verify_lsag_with_root() {
+        let ring = Ring::new(get_points(&[2, 3, 4])).unwrap();
+        let root = ring_root(&ring).unwrap();
+
+        let signature = sign_lsag(&ring, Scalar::from(3u64), "message".to_string(), None).unwrap();
+        assert!(verify_lsag_with_root(signature, &root));
+
+        // the signer is not part of the authorized set
+        let signature = sign_lsag(&ring, Scalar::from(42u64), "message".to_string(), None).unwrap();
+        assert!(!verify_lsag_with_root(signature, &root));
+    }
+}
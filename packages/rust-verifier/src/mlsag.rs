@@ -0,0 +1,484 @@
+//! MLSAG: multilayer linkable ring signatures.
+//!
+//! Each ring member is a vector of keys (e.g. a spend key and a commitment key), one per layer,
+//! and the signer proves the knowledge of all the private keys of its member. The first layers
+//! are linkable: they have a key image each, computed as for LSAG signatures
+//! (`x * H(K, flag)`), so the key image of the first layer links with the LSAG signatures of the
+//! same key. The other layers are not linkable.
+//!
+//! Only secp256k1 is supported, and there is no TS implementation yet.
+use crate::lsag_verifier::compute_mapped_point;
+use crate::utils::base64_json::decode_base64_json;
+use crate::utils::curve::{Curve, RingCurve};
+use crate::utils::hex_to_decimal::hex_to_decimal;
+use crate::utils::keccak256::keccak_256;
+use crate::utils::linkability_flag::parse_linkability_flag;
+use base64::engine::general_purpose;
+use base64::Engine;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// The JSON representation of a MLSAG signature, in the format of the LSAG signatures
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StringifiedMlsag {
+    pub message: String,
+    pub ring: Vec<Vec<String>>,
+    pub c: String,
+    pub responses: Vec<Vec<String>>,
+    pub curve: String,
+    pub keyImages: Vec<String>,
+    pub linkabilityFlag: String,
+}
+
+/// A MLSAG signature on secp256k1
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mlsag {
+    /// The keys of each ring member, one per layer
+    pub ring: Vec<Vec<AffinePoint>>,
+    pub message: String,
+    pub c0: Scalar,
+    /// The responses of each ring member, one per layer
+    pub responses: Vec<Vec<Scalar>>,
+    /// The key images of the linkable layers, which are the first layers
+    pub key_images: Vec<AffinePoint>,
+    pub linkability_flag: Option<String>,
+}
+
+impl Mlsag {
+    /// Parses a MLSAG signature from its JSON string
+    pub fn from_json(json: &str) -> Result<Mlsag, String> {
+        let stringified: StringifiedMlsag =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        Mlsag::try_from(stringified)
+    }
+
+    /// Converts the signature to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedMlsag::from(self)).unwrap()
+    }
+
+    /// Parses a MLSAG signature from the base64 encoding of its JSON string
+    pub fn from_base64(b64_signature: &str) -> Result<Mlsag, String> {
+        Mlsag::from_json(&decode_base64_json(b64_signature)?)
+    }
+
+    /// Converts the signature to the base64 encoding of its JSON string
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.to_json())
+    }
+
+    /// Returns the number of keys of each ring member
+    pub fn layers(&self) -> usize {
+        self.ring.first().map_or(0, Vec::len)
+    }
+}
+
+impl TryFrom<StringifiedMlsag> for Mlsag {
+    type Error = String;
+
+    fn try_from(json: StringifiedMlsag) -> Result<Mlsag, String> {
+        let curve = Curve::from_json_string(&json.curve)?;
+        if curve != Curve::Secp256k1 {
+            return Err(format!("Unsupported curve: {}", curve.name()));
+        }
+
+        let signature = Mlsag {
+            ring: json
+                .ring
+                .iter()
+                .map(|member| {
+                    member
+                        .iter()
+                        .map(|point| Secp256k1::deserialize_point(point))
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<AffinePoint>>, String>>()?,
+            message: json.message,
+            c0: Secp256k1::scalar_from_hex(&json.c)?,
+            responses: json
+                .responses
+                .iter()
+                .map(|responses| {
+                    responses
+                        .iter()
+                        .map(|response| Secp256k1::scalar_from_hex(response))
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<Scalar>>, String>>()?,
+            key_images: json
+                .keyImages
+                .iter()
+                .map(|point| Secp256k1::deserialize_point(point))
+                .collect::<Result<Vec<AffinePoint>, String>>()?,
+            // `toJsonString()` writes a missing flag as an empty string
+            linkability_flag: parse_linkability_flag(json.linkabilityFlag),
+        };
+        check_dimensions(&signature)?;
+
+        Ok(signature)
+    }
+}
+
+impl From<&Mlsag> for StringifiedMlsag {
+    fn from(signature: &Mlsag) -> StringifiedMlsag {
+        StringifiedMlsag {
+            message: signature.message.clone(),
+            ring: signature
+                .ring
+                .iter()
+                .map(|member| member.iter().map(Secp256k1::serialize_point).collect())
+                .collect(),
+            c: Secp256k1::scalar_to_hex(&signature.c0),
+            responses: signature
+                .responses
+                .iter()
+                .map(|responses| responses.iter().map(Secp256k1::scalar_to_hex).collect())
+                .collect(),
+            curve: Curve::Secp256k1.to_json_string(),
+            keyImages: signature
+                .key_images
+                .iter()
+                .map(Secp256k1::serialize_point)
+                .collect(),
+            linkabilityFlag: signature.linkability_flag.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Parameters required for the compute_c function
+pub struct Params<'a> {
+    pub previous_r: &'a [Scalar],
+    pub previous_c: Scalar,
+    pub previous_index: usize,
+    pub linkability_flag: &'a Option<String>,
+    pub key_images: &'a [AffinePoint],
+}
+
+/// Computes the 'cee' value following the ring member of index `previous_index`
+pub fn compute_c(
+    ring: &[Vec<AffinePoint>],
+    serialized_ring: &str,
+    message_digest: &str,
+    params: &Params,
+) -> Scalar {
+    let member = &ring[params.previous_index];
+
+    let points: Vec<(AffinePoint, Option<AffinePoint>)> = member
+        .iter()
+        .zip(params.previous_r)
+        .enumerate()
+        .map(|(layer, (public_key, r))| {
+            let point =
+                Secp256k1::lincomb(&Secp256k1::generator(), r, public_key, &params.previous_c);
+            let mapped_point = params.key_images.get(layer).map(|key_image| {
+                let mapped = compute_mapped_point::<Secp256k1>(public_key, params.linkability_flag);
+                Secp256k1::lincomb(&mapped, r, key_image, &params.previous_c)
+            });
+
+            (point, mapped_point)
+        })
+        .collect();
+
+    compute_c_from_points(serialized_ring, message_digest, &points)
+}
+
+/// Computes the 'cee' value from the points of every layer of a ring iteration:
+/// `r*G + c*K` and, for the linkable layers, `r*H(K, flag) + c*I`
+/// (or `alpha*G` and `alpha*H(K, flag)` for the signer)
+pub fn compute_c_from_points(
+    serialized_ring: &str,
+    message_digest: &str,
+    points: &[(AffinePoint, Option<AffinePoint>)],
+) -> Scalar {
+    let mut hash_content = serialized_ring.to_string() + &hex_to_decimal(message_digest).unwrap();
+    for (point, mapped_point) in points {
+        hash_content += &Secp256k1::serialize_point(point);
+        if let Some(mapped_point) = mapped_point {
+            hash_content += &Secp256k1::serialize_point(mapped_point);
+        }
+    }
+
+    let hash = Keccak256::digest(hash_content.as_bytes());
+
+    Secp256k1::scalar_from_hash(&hash)
+}
+
+/// Signs a message using a MLSAG ring signature.
+///
+/// The ring is used as given and must contain the signer member, whose keys are the
+/// `signer_private_keys` times G. The first `linkable_layers` layers get a key image: there
+/// must be at least one, and the verifier expects the same number.
+pub fn sign_mlsag(
+    ring: &[Vec<AffinePoint>],
+    signer_private_keys: &[Scalar],
+    linkable_layers: usize,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<Mlsag, String> {
+    if signer_private_keys.is_empty() {
+        return Err("The signer must have at least one private key".to_string());
+    }
+    if signer_private_keys
+        .iter()
+        .any(|private_key| bool::from(private_key.is_zero()))
+    {
+        return Err("Signer private key cannot be 0".to_string());
+    }
+    if linkable_layers == 0 {
+        return Err("There must be at least one linkable layer".to_string());
+    }
+    if linkable_layers > signer_private_keys.len() {
+        return Err("There cannot be more linkable layers than layers".to_string());
+    }
+
+    let signer_member: Vec<AffinePoint> = signer_private_keys
+        .iter()
+        .map(Secp256k1::mul_generator)
+        .collect();
+    let signer_index = ring
+        .iter()
+        .position(|member| *member == signer_member)
+        .ok_or_else(|| "The signer keys are not in the ring".to_string())?;
+    if ring
+        .iter()
+        .any(|member| member.len() != signer_private_keys.len())
+    {
+        return Err("Every ring member must have the same number of keys".to_string());
+    }
+
+    let message_digest = keccak_256(std::slice::from_ref(&message));
+    let serialized_ring = serialize_ring(ring);
+
+    let mapped: Vec<AffinePoint> = signer_member[..linkable_layers]
+        .iter()
+        .map(|public_key| compute_mapped_point::<Secp256k1>(public_key, &linkability_flag))
+        .collect();
+    let key_images: Vec<AffinePoint> = mapped
+        .iter()
+        .zip(signer_private_keys)
+        .map(|(mapped, private_key)| Secp256k1::mul(mapped, private_key))
+        .collect();
+
+    let alphas: Vec<Scalar> = signer_private_keys
+        .iter()
+        .map(|_| Secp256k1::random_scalar())
+        .collect();
+
+    // generate random responses for every key of every ring member
+    let mut responses: Vec<Vec<Scalar>> = ring
+        .iter()
+        .map(|member| member.iter().map(|_| Secp256k1::random_scalar()).collect())
+        .collect();
+
+    // contains all the cees from 0 to ring.len() - 1 (0, 1, ..., pi, ..., ring.len() - 1)
+    let mut cees = vec![Scalar::ZERO; ring.len()];
+
+    // compute cpi+1
+    let signer_points: Vec<(AffinePoint, Option<AffinePoint>)> = alphas
+        .iter()
+        .enumerate()
+        .map(|(layer, alpha)| {
+            (
+                Secp256k1::mul_generator(alpha),
+                mapped
+                    .get(layer)
+                    .map(|mapped| Secp256k1::mul(mapped, alpha)),
+            )
+        })
+        .collect();
+    cees[(signer_index + 1) % ring.len()] =
+        compute_c_from_points(&serialized_ring, &message_digest, &signer_points);
+
+    // compute the c values from pi+2 to pi (included)
+    for i in signer_index + 2..signer_index + ring.len() + 1 {
+        let index = i % ring.len();
+        let previous_index = (index + ring.len() - 1) % ring.len();
+
+        let params = Params {
+            previous_r: &responses[previous_index],
+            previous_c: cees[previous_index],
+            previous_index,
+            linkability_flag: &linkability_flag,
+            key_images: &key_images,
+        };
+
+        cees[index] = compute_c(ring, &serialized_ring, &message_digest, &params);
+    }
+
+    // compute the signer responses
+    responses[signer_index] = alphas
+        .iter()
+        .zip(signer_private_keys)
+        .map(|(alpha, private_key)| alpha - &(cees[signer_index] * private_key))
+        .collect();
+
+    Ok(Mlsag {
+        ring: ring.to_vec(),
+        message,
+        c0: cees[0],
+        responses,
+        key_images,
+        linkability_flag,
+    })
+}
+
+/// Verifies a MLSAG signature whose first `linkable_layers` layers must have a key image.
+/// The number of linkable layers is set by the verifier, so that a signer cannot drop key
+/// images to avoid being linked.
+/// Returns `true` if the signature is valid, `false` otherwise.
+pub fn verify_mlsag(signature: Mlsag, linkable_layers: usize) -> bool {
+    if check_dimensions(&signature).is_err()
+        || linkable_layers == 0
+        || signature.key_images.len() != linkable_layers
+    {
+        return false;
+    }
+
+    let message_digest = keccak_256(std::slice::from_ref(&signature.message));
+    let serialized_ring = serialize_ring(&signature.ring);
+
+    // Compute the c values: c1', c2', ..., cn', c0'
+    let mut last_computed_c = signature.c0;
+    for (i, responses) in signature.responses.iter().enumerate() {
+        let params = Params {
+            previous_r: responses,
+            previous_c: last_computed_c,
+            previous_index: i,
+            linkability_flag: &signature.linkability_flag,
+            key_images: &signature.key_images,
+        };
+
+        last_computed_c = compute_c(&signature.ring, &serialized_ring, &message_digest, &params);
+    }
+
+    // Return true if c0 == c0'
+    signature.c0 == last_computed_c
+}
+
+/// Checks that the ring members, their responses and the key images have consistent lengths
+fn check_dimensions(signature: &Mlsag) -> Result<(), String> {
+    if signature.ring.is_empty() {
+        return Err("Ring cannot be empty".to_string());
+    }
+    if signature.ring.len() != signature.responses.len() {
+        return Err("Ring and responses must have the same length".to_string());
+    }
+
+    let layers = signature.layers();
+    if layers == 0 {
+        return Err("Ring members must have at least one key".to_string());
+    }
+    if signature
+        .ring
+        .iter()
+        .zip(&signature.responses)
+        .any(|(member, responses)| member.len() != layers || responses.len() != layers)
+    {
+        return Err("Every ring member must have one key and one response per layer".to_string());
+    }
+    if signature.key_images.is_empty() {
+        return Err("There must be at least one key image".to_string());
+    }
+    if signature.key_images.len() > layers {
+        return Err("There cannot be more key images than layers".to_string());
+    }
+
+    Ok(())
+}
+
+/// Concatenates the serialized keys of every ring member
+fn serialize_ring(ring: &[Vec<AffinePoint>]) -> String {
+    ring.iter()
+        .flatten()
+        .map(Secp256k1::serialize_point)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::utils::sort_ring::sort_ring;
+
+    fn get_ring(private_keys: &[[u64; 2]]) -> Vec<Vec<AffinePoint>> {
+        private_keys
+            .iter()
+            .map(|keys| {
+                keys.iter()
+                    .map(|k| Secp256k1::mul_generator(&Scalar::from(*k)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sign_mlsag() {
+        let ring = get_ring(&[[2, 3], [4, 5], [42, 43], [6, 7]]);
+        let signer_private_keys = [Scalar::from(42u64), Scalar::from(43u64)];
+        let flag = Some("linkability flag".to_string());
+
+        let signature = sign_mlsag(
+            &ring,
+            &signer_private_keys,
+            1,
+            "message".to_string(),
+            flag.clone(),
+        )
+        .expect("failed to sign");
+        assert_eq!(signature.layers(), 2);
+        assert_eq!(signature.key_images.len(), 1);
+        assert!(verify_mlsag(signature.clone(), 1));
+        assert_eq!(
+            Mlsag::from_base64(&signature.to_base64()),
+            Ok(signature.clone())
+        );
+
+        // the key image of the first layer links with the LSAG signatures of the same key
+        let mut lsag_ring = vec![ring[0][0], ring[1][0]];
+        sort_ring(&mut lsag_ring);
+        let lsag = sign_lsag(&lsag_ring, Scalar::from(42u64), "other".to_string(), flag).unwrap();
+        assert_eq!(signature.key_images[0], lsag.key_image);
+
+        let mut tampered = signature.clone();
+        tampered.message = "another message".to_string();
+        assert!(!verify_mlsag(tampered, 1));
+
+        // the verifier sets the number of key images
+        assert!(!verify_mlsag(signature.clone(), 2));
+        let mut missing_key_image = signature;
+        missing_key_image.key_images.clear();
+        assert!(!verify_mlsag(missing_key_image.clone(), 0));
+        assert!(!verify_mlsag(missing_key_image, 1));
+    }
+
+    #[test]
+    fn test_sign_mlsag_errors() {
+        let ring = get_ring(&[[2, 3], [4, 5]]);
+        let message = "message".to_string();
+
+        // the signer must know every key of its member
+        let wrong_keys = [Scalar::from(2u64), Scalar::from(5u64)];
+        assert!(sign_mlsag(&ring, &wrong_keys, 2, message.clone(), None).is_err());
+
+        let keys = [Scalar::from(2u64), Scalar::from(3u64)];
+        assert!(sign_mlsag(&ring, &keys, 3, message.clone(), None).is_err());
+        // a signature must be linkable
+        assert!(sign_mlsag(&ring, &keys, 0, message.clone(), None).is_err());
+
+        let signature = sign_mlsag(&ring, &keys, 2, message, None).unwrap();
+        assert_eq!(signature.key_images.len(), 2);
+        assert!(verify_mlsag(signature.clone(), 2));
+        // a signature without flag is parsed back without flag
+        assert_eq!(
+            Mlsag::from_json(&signature.to_json()),
+            Ok(signature.clone())
+        );
+
+        let mut missing_response = signature;
+        missing_response.responses[1].pop();
+        assert!(!verify_mlsag(missing_response, 2));
+    }
+}
@@ -0,0 +1,245 @@
+//! Anonymous Nostr events signed by a member of a ring of npubs.
+//!
+//! The LSAG signs the NIP-01 id of the event without its `lsag` tag. It is then embedded in an
+//! `["lsag", <base64 binary signature>]` tag, using the binary encoding with an empty message,
+//! and the event id is recomputed. The event itself is still signed by its own (usually
+//! throwaway) `pubkey` with BIP340, which is out of the scope of this module.
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::{verify_lsag, Lsag};
+use crate::ring::Ring;
+use crate::utils::serialize_point::{even_y_private_key, lift_x_only};
+use base64::engine::general_purpose;
+use base64::Engine;
+use bech32::{Bech32, Hrp};
+use elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The name of the tag holding the ring signature
+pub const LSAG_TAG: &str = "lsag";
+
+/// A NIP-01 event
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NostrEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The x-only hex public key of the event author
+    pub pubkey: String,
+    pub created_at: u64,
+    pub kind: u32,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
+}
+
+impl NostrEvent {
+    /// Computes the NIP-01 event id: the sha256 hash of
+    /// `[0, pubkey, created_at, kind, tags, content]`, as a hex string
+    pub fn compute_id(&self) -> String {
+        let serialized = serde_json::to_string(&(
+            0,
+            &self.pubkey,
+            self.created_at,
+            self.kind,
+            &self.tags,
+            &self.content,
+        ))
+        .unwrap();
+
+        hex::encode(Sha256::digest(serialized.as_bytes()))
+    }
+
+    /// Returns the event without its ring signature tag
+    fn without_lsag_tag(&self) -> NostrEvent {
+        let mut event = self.clone();
+        event
+            .tags
+            .retain(|tag| tag.first().map(String::as_str) != Some(LSAG_TAG));
+        event
+    }
+}
+
+/// Parses a NIP-19 npub, lifting the x-only key to the point with an even y
+pub fn parse_npub(npub: &str) -> Result<AffinePoint, String> {
+    let (hrp, data) = bech32::decode(npub).map_err(|e| format!("Invalid npub: {}", e))?;
+    if hrp.as_str() != "npub" {
+        return Err(format!("Invalid npub prefix: {}", hrp));
+    }
+
+    lift_x_only(&data)
+}
+
+/// Converts a public key to a NIP-19 npub
+pub fn to_npub(public_key: &AffinePoint) -> String {
+    let encoded = public_key.to_encoded_point(true);
+    bech32::encode::<Bech32>(Hrp::parse("npub").unwrap(), &encoded.as_bytes()[1..]).unwrap()
+}
+
+/// Signs an event on behalf of a ring of npubs and returns it with its `lsag` tag and its new id.
+///
+/// The signer private key is negated if needed, as BIP340 does, so that its public key
+/// is the even y point its npub is lifted to. Returns an error if the signer npub is not in
+/// `ring_npubs`.
+pub fn sign_nostr_event(
+    event: &NostrEvent,
+    ring_npubs: &[String],
+    signer_private_key: Scalar,
+    linkability_flag: Option<String>,
+) -> Result<NostrEvent, String> {
+    let ring = parse_ring(ring_npubs)?;
+
+    let signer_private_key = even_y_private_key(signer_private_key);
+    if !ring.contains(&(AffinePoint::GENERATOR * signer_private_key).to_affine()) {
+        return Err("The signer npub is not a member of the ring".to_string());
+    }
+
+    let mut event = event.without_lsag_tag();
+    let mut signature = sign_lsag(
+        &ring,
+        signer_private_key,
+        event.compute_id(),
+        linkability_flag,
+    )?;
+
+    // the message is re-derived from the event by the verifier
+    signature.message = String::new();
+    event.tags.push(vec![
+        LSAG_TAG.to_string(),
+        general_purpose::STANDARD.encode(signature.to_bytes()?),
+    ]);
+    event.id = Some(event.compute_id());
+    event.sig = None;
+
+    Ok(event)
+}
+
+/// Verifies the ring signature of an event and returns it.
+///
+/// Checks the event id if it is set, re-derives the signed message from the event without
+/// its `lsag` tag, and verifies the LSAG. The ring of the signature must be exactly the set of
+/// `ring_npubs`, the known group the event is expected from.
+pub fn verify_nostr_event(event: &NostrEvent, ring_npubs: &[String]) -> Result<Lsag, String> {
+    if let Some(id) = &event.id {
+        if *id != event.compute_id() {
+            return Err("Invalid event id".to_string());
+        }
+    }
+
+    let lsag_tags: Vec<&Vec<String>> = event
+        .tags
+        .iter()
+        .filter(|tag| tag.first().map(String::as_str) == Some(LSAG_TAG))
+        .collect();
+    let b64_signature = match lsag_tags.as_slice() {
+        [tag] if tag.len() == 2 => &tag[1],
+        [] => return Err("Missing lsag tag".to_string()),
+        _ => return Err("An event must have a single valid lsag tag".to_string()),
+    };
+
+    let signature_bytes = general_purpose::STANDARD
+        .decode(b64_signature)
+        .map_err(|e| format!("Invalid base64 string: {}", e))?;
+    let mut signature = Lsag::from_bytes(&signature_bytes)?;
+
+    if signature.ring != parse_ring(ring_npubs)?.points() {
+        return Err("The ring of the signature does not match the expected ring".to_string());
+    }
+
+    signature.message = event.without_lsag_tag().compute_id();
+    if !verify_lsag(signature.clone()) {
+        return Err("Invalid ring signature".to_string());
+    }
+
+    Ok(signature)
+}
+
+/// Builds the canonical ring of a list of npubs
+fn parse_ring(ring_npubs: &[String]) -> Result<Ring, String> {
+    Ring::new(
+        ring_npubs
+            .iter()
+            .map(|npub| parse_npub(npub))
+            .collect::<Result<Vec<AffinePoint>, String>>()?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_npubs(private_keys: &[u64]) -> Vec<String> {
+        private_keys
+            .iter()
+            .map(|k| to_npub(&(AffinePoint::GENERATOR * Scalar::from(*k)).to_affine()))
+            .collect()
+    }
+
+    fn get_event() -> NostrEvent {
+        NostrEvent {
+            id: None,
+            pubkey: "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e".to_string(),
+            created_at: 1729346400,
+            kind: 1,
+            tags: vec![vec!["t".to_string(), "dao".to_string()]],
+            content: "An anonymous \"member\" says:\nhello".to_string(),
+            sig: None,
+        }
+    }
+
+    #[test]
+    fn test_npub() {
+        // test vector from NIP-19
+        let npub = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+        let public_key = parse_npub(npub).unwrap();
+
+        assert_eq!(
+            hex::encode(&public_key.to_encoded_point(true).as_bytes()[1..]),
+            "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e"
+        );
+        assert_eq!(to_npub(&public_key), npub);
+        let nsec = bech32::encode::<Bech32>(Hrp::parse("nsec").unwrap(), &[1u8; 32]).unwrap();
+        assert!(parse_npub(&nsec).is_err());
+        assert!(
+            parse_npub("npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjpth").is_err()
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify_nostr_event() {
+        let ring_npubs = get_npubs(&[2, 3, 4, 5, 6]);
+
+        // the public keys of some of these private keys have an odd y
+        for private_key in [2u64, 3, 4, 5, 6] {
+            let event = sign_nostr_event(
+                &get_event(),
+                &ring_npubs,
+                Scalar::from(private_key),
+                Some("dao-board".to_string()),
+            )
+            .expect("failed to sign the event");
+
+            assert_eq!(event.id, Some(event.compute_id()));
+            assert!(verify_nostr_event(&event, &ring_npubs).is_ok());
+        }
+
+        let event = sign_nostr_event(&get_event(), &ring_npubs, Scalar::from(2u64), None).unwrap();
+        assert!(verify_nostr_event(&event, &get_npubs(&[2, 3])).is_err());
+
+        let mut tampered = event.clone();
+        tampered.content = "hello".to_string();
+        assert!(verify_nostr_event(&tampered, &ring_npubs).is_err());
+        // recomputing the id does not help
+        tampered.id = Some(tampered.compute_id());
+        assert!(verify_nostr_event(&tampered, &ring_npubs).is_err());
+
+        // a signer outside of the ring cannot sign, even with a ring of its own
+        assert!(sign_nostr_event(&get_event(), &ring_npubs, Scalar::from(42u64), None).is_err());
+        let own_ring = get_npubs(&[2, 3, 42]);
+        let outsider =
+            sign_nostr_event(&get_event(), &own_ring, Scalar::from(42u64), None).unwrap();
+        assert!(verify_nostr_event(&outsider, &own_ring).is_ok());
+        assert!(verify_nostr_event(&outsider, &ring_npubs).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::binary_encoding::RingSignature;
+use crate::utils::curve::Curve;
+use crate::utils::eth_address::{eth_address_bytes, to_eth_address};
+use crate::utils::serialize_point::serialize_point;
+use crate::utils::signature_config::HashFunction;
+use core::fmt;
+use k256::AffinePoint;
+use std::collections::HashSet;
+
+/// The rules a signature must comply with, on top of being mathematically valid.
+/// Rules set to `None` (or empty for `revoked`) are not checked.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerificationPolicy {
+    /// Every ring member must be in the allowlist
+    pub allowlist: Option<Vec<AffinePoint>>,
+    /// The ethereum address of every ring member must be in the allowlist
+    /// (see `parse_eth_address` to parse addresses)
+    pub address_allowlist: Option<Vec<[u8; 20]>>,
+    /// No ring member can be revoked
+    pub revoked: Vec<AffinePoint>,
+    pub min_ring_size: Option<usize>,
+    pub max_ring_size: Option<usize>,
+    pub curve: Option<Curve>,
+    /// Signatures without config use keccak256
+    pub hash: Option<HashFunction>,
+    /// The linkability flag must start with this prefix. SAG signatures, which have no
+    /// linkability flag, are rejected if it is set.
+    pub linkability_flag_prefix: Option<String>,
+}
+
+/// The policy rule a signature does not comply with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyViolation {
+    RingTooSmall {
+        size: usize,
+        min: usize,
+    },
+    RingTooLarge {
+        size: usize,
+        max: usize,
+    },
+    UnexpectedCurve {
+        expected: Curve,
+        actual: Curve,
+    },
+    UnexpectedHash {
+        expected: HashFunction,
+        actual: HashFunction,
+    },
+    InvalidLinkabilityFlag {
+        prefix: String,
+    },
+    /// The compressed hex public key of the ring member which is not in the allowlist
+    NotAllowed(String),
+    /// The checksummed ethereum address of the ring member which is not in the address allowlist
+    AddressNotAllowed(String),
+    /// The compressed hex public key of the revoked ring member
+    Revoked(String),
+    /// The signature complies with the policy but is not valid
+    InvalidSignature,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyViolation::RingTooSmall { size, min } => {
+                write!(f, "Ring size {} is lower than the minimum {}", size, min)
+            }
+            PolicyViolation::RingTooLarge { size, max } => {
+                write!(f, "Ring size {} is greater than the maximum {}", size, max)
+            }
+            PolicyViolation::UnexpectedCurve { expected, actual } => write!(
+                f,
+                "Unexpected curve: expected {}, got {}",
+                expected.name(),
+                actual.name()
+            ),
+            PolicyViolation::UnexpectedHash { expected, actual } => write!(
+                f,
+                "Unexpected hash function: expected {}, got {}",
+                expected.name(),
+                actual.name()
+            ),
+            PolicyViolation::InvalidLinkabilityFlag { prefix } => {
+                write!(f, "The linkability flag must start with '{}'", prefix)
+            }
+            PolicyViolation::NotAllowed(public_key) => {
+                write!(f, "Ring member {} is not in the allowlist", public_key)
+            }
+            PolicyViolation::AddressNotAllowed(address) => {
+                write!(f, "Ring member {} is not in the address allowlist", address)
+            }
+            PolicyViolation::Revoked(public_key) => {
+                write!(f, "Ring member {} is revoked", public_key)
+            }
+            PolicyViolation::InvalidSignature => write!(f, "Invalid ring signature"),
+        }
+    }
+}
+
+impl VerificationPolicy {
+    /// Checks the policy rules, without verifying the signature itself
+    pub fn check(&self, signature: &RingSignature) -> Result<(), PolicyViolation> {
+        let size = signature.ring().len();
+        if let Some(min) = self.min_ring_size {
+            if size < min {
+                return Err(PolicyViolation::RingTooSmall { size, min });
+            }
+        }
+        if let Some(max) = self.max_ring_size {
+            if size > max {
+                return Err(PolicyViolation::RingTooLarge { size, max });
+            }
+        }
+
+        if let Some(expected) = self.curve {
+            if signature.curve() != expected {
+                return Err(PolicyViolation::UnexpectedCurve {
+                    expected,
+                    actual: signature.curve(),
+                });
+            }
+        }
+        if let Some(expected) = self.hash {
+            if signature.hash() != expected {
+                return Err(PolicyViolation::UnexpectedHash {
+                    expected,
+                    actual: signature.hash(),
+                });
+            }
+        }
+
+        if let Some(prefix) = &self.linkability_flag_prefix {
+            if !signature
+                .linkability_flag()
+                .is_some_and(|flag| flag.starts_with(prefix.as_str()))
+            {
+                return Err(PolicyViolation::InvalidLinkabilityFlag {
+                    prefix: prefix.clone(),
+                });
+            }
+        }
+
+        let members: Vec<String> = signature
+            .ring()
+            .iter()
+            .map(|point| serialize_point(*point))
+            .collect();
+
+        let revoked = to_set(&self.revoked);
+        if let Some(member) = members.iter().find(|member| revoked.contains(*member)) {
+            return Err(PolicyViolation::Revoked(member.clone()));
+        }
+
+        if let Some(allowlist) = &self.allowlist {
+            let allowlist = to_set(allowlist);
+            if let Some(member) = members.iter().find(|member| !allowlist.contains(*member)) {
+                return Err(PolicyViolation::NotAllowed(member.clone()));
+            }
+        }
+
+        if let Some(address_allowlist) = &self.address_allowlist {
+            let address_allowlist: HashSet<&[u8; 20]> = address_allowlist.iter().collect();
+            if let Some(member) = signature
+                .ring()
+                .iter()
+                .find(|point| !address_allowlist.contains(&eth_address_bytes(point)))
+            {
+                return Err(PolicyViolation::AddressNotAllowed(to_eth_address(member)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the policy rules, then verifies the signature.
+/// Returns the first rule the signature does not comply with.
+pub fn verify_with_policy(
+    signature: &RingSignature,
+    policy: &VerificationPolicy,
+) -> Result<(), PolicyViolation> {
+    policy.check(signature)?;
+
+    if !signature.verify() {
+        return Err(PolicyViolation::InvalidSignature);
+    }
+    Ok(())
+}
+
+fn to_set(points: &[AffinePoint]) -> HashSet<String> {
+    points.iter().map(|point| serialize_point(*point)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::ring::Ring;
+    use crate::sag_signer::sign_sag;
+    use crate::utils::test_utils::get_points;
+    use k256::Scalar;
+
+    fn sign(ring: &Ring, flag: &str) -> RingSignature {
+        RingSignature::Lsag(
+            sign_lsag(
+                ring,
+                Scalar::from(3u64),
+                "message".to_string(),
+                Some(flag.to_string()),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_verify_with_policy() {
+        let members = get_points(&[2, 3, 4, 5, 6]);
+        let ring = Ring::new(get_points(&[2, 3, 4])).unwrap();
+        let signature = sign(&ring, "vote:42");
+
+        let policy = VerificationPolicy {
+            allowlist: Some(members.clone()),
+            address_allowlist: Some(members.iter().map(eth_address_bytes).collect()),
+            revoked: get_points(&[6]),
+            min_ring_size: Some(3),
+            max_ring_size: Some(10),
+            curve: Some(Curve::Secp256k1),
+            hash: Some(HashFunction::Keccak256),
+            linkability_flag_prefix: Some("vote:".to_string()),
+        };
+        assert_eq!(verify_with_policy(&signature, &policy), Ok(()));
+        assert_eq!(
+            verify_with_policy(&signature, &VerificationPolicy::default()),
+            Ok(())
+        );
+
+        let mut tampered = signature.clone();
+        if let RingSignature::Lsag(lsag) = &mut tampered {
+            lsag.message = "other message".to_string();
+        }
+        assert_eq!(
+            verify_with_policy(&tampered, &policy),
+            Err(PolicyViolation::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let ring = Ring::new(get_points(&[2, 3, 4])).unwrap();
+        let signature = sign(&ring, "poll:1");
+
+        let policy = VerificationPolicy {
+            min_ring_size: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.check(&signature),
+            Err(PolicyViolation::RingTooSmall { size: 3, min: 4 })
+        );
+
+        let policy = VerificationPolicy {
+            max_ring_size: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.check(&signature),
+            Err(PolicyViolation::RingTooLarge { size: 3, max: 2 })
+        );
+
+        let policy = VerificationPolicy {
+            hash: Some(HashFunction::Sha512),
+            ..Default::default()
+        };
+        assert!(matches!(
+            policy.check(&signature),
+            Err(PolicyViolation::UnexpectedHash { .. })
+        ));
+
+        let policy = VerificationPolicy {
+            linkability_flag_prefix: Some("vote:".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            policy.check(&signature),
+            Err(PolicyViolation::InvalidLinkabilityFlag { .. })
+        ));
+        let sag = RingSignature::Sag(
+            sign_sag(&ring, Scalar::from(3u64), "message".to_string(), None).unwrap(),
+        );
+        assert!(policy.check(&sag).is_err());
+
+        let policy = VerificationPolicy {
+            revoked: get_points(&[4]),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.check(&signature),
+            Err(PolicyViolation::Revoked(serialize_point(
+                get_points(&[4])[0]
+            )))
+        );
+
+        let policy = VerificationPolicy {
+            allowlist: Some(get_points(&[2, 3])),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.check(&signature),
+            Err(PolicyViolation::NotAllowed(serialize_point(
+                get_points(&[4])[0]
+            )))
+        );
+    }
+
+    #[test]
+    fn test_address_allowlist() {
+        let ring = Ring::new(get_points(&[2, 3, 4])).unwrap();
+        let signature = sign(&ring, "flag");
+
+        let policy = VerificationPolicy {
+            address_allowlist: Some(get_points(&[2, 3]).iter().map(eth_address_bytes).collect()),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.check(&signature),
+            Err(PolicyViolation::AddressNotAllowed(to_eth_address(
+                &get_points(&[4])[0]
+            )))
+        );
+    }
+}
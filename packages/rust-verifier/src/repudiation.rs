@@ -0,0 +1,160 @@
+//! Repudiation proofs: a ring member proves that they did not sign a LSAG signature.
+//!
+//! The key image of a member under a linkability flag is `x * H(K, flag)`. A member who did not
+//! sign publishes their own key image under the flag of the signature, which differs from the
+//! signature key image, with a DLEQ proof (see `dleq`) that it uses the private key of
+//! `K = x * G`. The private key is not revealed, but the member's signatures under the same flag
+//! become linkable to their public key.
+use crate::dleq::{prove_key_image, verify_key_image, DleqProof, StringifiedDleqProof};
+use crate::lsag_verifier::Lsag;
+use crate::utils::curve::RingCurve;
+use crate::utils::signature_config::HashFunction;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+
+const PROOF_DOMAIN: &str = "non_authorship";
+
+/// The JSON representation of a non-authorship proof
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StringifiedNonAuthorshipProof {
+    pub publicKey: String,
+    pub keyImage: String,
+    #[serde(flatten)]
+    pub proof: StringifiedDleqProof,
+}
+
+/// A proof that the holder of `public_key` did not sign a LSAG signature
+#[derive(Clone, Debug, PartialEq)]
+pub struct NonAuthorshipProof {
+    pub public_key: AffinePoint,
+    /// The key image of `public_key` under the linkability flag of the signature
+    pub key_image: AffinePoint,
+    /// The proof that `key_image` is the key image of `public_key`
+    pub proof: DleqProof,
+}
+
+impl NonAuthorshipProof {
+    /// Parses a proof from its JSON string
+    pub fn from_json(json: &str) -> Result<NonAuthorshipProof, String> {
+        let stringified: StringifiedNonAuthorshipProof =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        Ok(NonAuthorshipProof {
+            public_key: Secp256k1::deserialize_point(&stringified.publicKey)?,
+            key_image: Secp256k1::deserialize_point(&stringified.keyImage)?,
+            proof: DleqProof::try_from(stringified.proof)?,
+        })
+    }
+
+    /// Converts the proof to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedNonAuthorshipProof {
+            publicKey: Secp256k1::serialize_point(&self.public_key),
+            keyImage: Secp256k1::serialize_point(&self.key_image),
+            proof: StringifiedDleqProof::from(&self.proof),
+        })
+        .unwrap()
+    }
+}
+
+/// Proves that the holder of `secret` did not sign `signature`.
+/// `flag` must be the linkability flag of the signature.
+pub fn prove_non_authorship(
+    secret: &Scalar,
+    flag: Option<String>,
+    signature: &Lsag,
+) -> Result<NonAuthorshipProof, String> {
+    if flag.as_deref().unwrap_or("") != signature.linkability_flag.as_deref().unwrap_or("") {
+        return Err("The flag does not match the linkability flag of the signature".to_string());
+    }
+
+    let public_key = Secp256k1::mul_generator(secret);
+    if !signature.ring.contains(&public_key) {
+        return Err("The public key is not a member of the ring".to_string());
+    }
+
+    let (key_image, proof) = prove_key_image(
+        secret,
+        &flag,
+        &proof_context(signature),
+        HashFunction::Keccak256,
+    );
+    if key_image == signature.key_image {
+        return Err("The signature was signed with this key".to_string());
+    }
+
+    Ok(NonAuthorshipProof {
+        public_key,
+        key_image,
+        proof,
+    })
+}
+
+/// Verifies a non-authorship proof against the signature it repudiates.
+/// Returns `true` if the holder of the proof public key is a ring member who did not sign,
+/// `false` otherwise.
+pub fn verify_non_authorship(proof: &NonAuthorshipProof, signature: &Lsag) -> bool {
+    // the holder must be a ring member whose key image is not the signature one
+    if !signature.ring.contains(&proof.public_key) || proof.key_image == signature.key_image {
+        return false;
+    }
+
+    verify_key_image(
+        &proof.proof,
+        &proof.public_key,
+        &proof.key_image,
+        &signature.linkability_flag,
+        &proof_context(signature),
+    )
+}
+
+/// Binds the proof to the repudiated signature
+fn proof_context(signature: &Lsag) -> String {
+    PROOF_DOMAIN.to_string()
+        + &Secp256k1::serialize_point(&signature.key_image)
+        + &signature.message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_non_authorship() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let flag = Some("leak".to_string());
+        let signature = sign_lsag(
+            &ring,
+            Scalar::from(4u64),
+            "document".to_string(),
+            flag.clone(),
+        )
+        .unwrap();
+
+        let proof = prove_non_authorship(&Scalar::from(2u64), flag.clone(), &signature)
+            .expect("failed to prove");
+        assert!(verify_non_authorship(&proof, &signature));
+        assert_eq!(
+            NonAuthorshipProof::from_json(&proof.to_json()),
+            Ok(proof.clone())
+        );
+
+        // the signer cannot repudiate the signature
+        assert!(prove_non_authorship(&Scalar::from(4u64), flag.clone(), &signature).is_err());
+        assert!(prove_non_authorship(&Scalar::from(3u64), None, &signature).is_err());
+        assert!(prove_non_authorship(&Scalar::from(42u64), flag, &signature).is_err());
+
+        // a proof cannot be moved to another public key
+        let mut moved = proof.clone();
+        moved.public_key = Secp256k1::mul_generator(&Scalar::from(3u64));
+        assert!(!verify_non_authorship(&moved, &signature));
+
+        // the key image must be the one of the public key
+        let mut forged = proof;
+        forged.key_image = Secp256k1::mul_generator(&Scalar::from(7u64));
+        assert!(!verify_non_authorship(&forged, &signature));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::utils::curve::RingCurve;
+use crate::utils::keccak256::keccak_256;
+use core::cmp::Ordering;
+use core::ops::Deref;
+use k256::Secp256k1;
+
+/// A ring of public keys, in canonical order (sorted by x ascending coordinate, then y)
+/// and without duplicates, as the TS libraries build them.
+///
+/// The serialized ring is cached since it is hashed for every 'cee' value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenericRing<C: RingCurve> {
+    points: Vec<C::Point>,
+    serialized: String,
+}
+
+/// A ring of secp256k1 public keys
+pub type Ring = GenericRing<Secp256k1>;
+
+impl<C: RingCurve> GenericRing<C> {
+    /// Builds a ring from a list of public keys, sorting them in canonical order.
+    /// Returns an error if the list is empty or contains duplicates.
+    pub fn new(mut points: Vec<C::Point>) -> Result<GenericRing<C>, String> {
+        points.sort_by(C::compare_points);
+        GenericRing::from_sorted(points)
+    }
+
+    /// Builds a ring from a list of public keys which must already be in canonical order
+    pub fn from_sorted(points: Vec<C::Point>) -> Result<GenericRing<C>, String> {
+        if points.is_empty() {
+            return Err("Ring cannot be empty".to_string());
+        }
+        if points.contains(&C::Point::default()) {
+            return Err("Ring cannot contain the point at infinity".to_string());
+        }
+        if !is_sorted::<C>(&points) {
+            return Err("The ring is not sorted".to_string());
+        }
+        if points.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("Duplicates points found in the ring".to_string());
+        }
+
+        let serialized = points.iter().map(C::serialize_point).collect();
+        Ok(GenericRing { points, serialized })
+    }
+
+    /// Builds a ring from compressed hex public keys, sorting them in canonical order
+    pub fn from_hex(points: &[String]) -> Result<GenericRing<C>, String> {
+        GenericRing::new(
+            points
+                .iter()
+                .map(|point| C::deserialize_point(point))
+                .collect::<Result<Vec<C::Point>, String>>()?,
+        )
+    }
+
+    /// Returns true if the points are in canonical order and without duplicates
+    pub fn is_canonical(points: &[C::Point]) -> bool {
+        is_sorted::<C>(points) && points.windows(2).all(|pair| pair[0] != pair[1])
+    }
+
+    /// Returns the public keys of the ring
+    pub fn points(&self) -> &[C::Point] {
+        &self.points
+    }
+
+    /// Returns the ring serialized as `serialize_ring` does
+    pub fn serialized(&self) -> &str {
+        &self.serialized
+    }
+
+    /// Returns a stable identifier of the ring: the keccak256 hash of the serialized ring,
+    /// as a hex string. Since the ring is canonical, it does not depend on the order
+    /// the public keys were provided in.
+    pub fn ring_id(&self) -> String {
+        keccak_256(std::slice::from_ref(&self.serialized))
+    }
+
+    /// Returns the index of a public key in the ring
+    pub fn position(&self, point: &C::Point) -> Option<usize> {
+        self.points.iter().position(|p| p == point)
+    }
+
+    /// Returns a new ring containing `point`, inserted at its canonical position,
+    /// and the index of `point` in that ring
+    pub fn with_member(&self, point: C::Point) -> Result<(GenericRing<C>, usize), String> {
+        let ring = if self.points.contains(&point) {
+            self.clone()
+        } else {
+            let mut points = self.points.clone();
+            points.push(point);
+            GenericRing::new(points)?
+        };
+        let index = ring.position(&point).unwrap();
+
+        Ok((ring, index))
+    }
+
+    /// Returns the public keys of the ring
+    pub fn into_points(self) -> Vec<C::Point> {
+        self.points
+    }
+}
+
+impl<C: RingCurve> Deref for GenericRing<C> {
+    type Target = [C::Point];
+
+    fn deref(&self) -> &[C::Point] {
+        &self.points
+    }
+}
+
+impl<C: RingCurve> TryFrom<Vec<C::Point>> for GenericRing<C> {
+    type Error = String;
+
+    fn try_from(points: Vec<C::Point>) -> Result<GenericRing<C>, String> {
+        GenericRing::new(points)
+    }
+}
+
+fn is_sorted<C: RingCurve>(points: &[C::Point]) -> bool {
+    points
+        .windows(2)
+        .all(|pair| C::compare_points(&pair[0], &pair[1]) != Ordering::Greater)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ring;
+    use crate::utils::test_utils::get_points;
+    use k256::AffinePoint;
+
+    #[test]
+    fn test_canonical_ring() {
+        let ring = Ring::new(get_points(&[5, 2, 4, 3])).expect("failed to build the ring");
+        let other_ring = Ring::new(get_points(&[3, 4, 2, 5])).unwrap();
+
+        assert!(Ring::is_canonical(&ring));
+        assert_eq!(ring, other_ring);
+        assert_eq!(ring.ring_id(), other_ring.ring_id());
+        assert_eq!(ring.ring_id().len(), 64);
+        assert_ne!(
+            ring.ring_id(),
+            Ring::new(get_points(&[2, 3])).unwrap().ring_id()
+        );
+
+        // from_sorted does not reorder the points
+        let mut unsorted = ring.points().to_vec();
+        unsorted.reverse();
+        assert!(!Ring::is_canonical(&unsorted));
+        assert!(Ring::from_sorted(unsorted).is_err());
+        assert_eq!(Ring::from_sorted(ring.points().to_vec()), Ok(ring));
+    }
+
+    #[test]
+    fn test_invalid_rings() {
+        assert!(Ring::new(vec![]).is_err());
+        assert!(Ring::new(get_points(&[2, 3, 2])).is_err());
+        assert!(Ring::new(vec![AffinePoint::IDENTITY]).is_err());
+    }
+
+    #[test]
+    fn test_with_member() {
+        let ring = Ring::new(get_points(&[2, 3, 4])).unwrap();
+        let member = get_points(&[42])[0];
+
+        let (new_ring, index) = ring.with_member(member).unwrap();
+        assert_eq!(new_ring.len(), 4);
+        assert_eq!(new_ring[index], member);
+        assert!(Ring::is_canonical(&new_ring));
+
+        let (same_ring, _) = new_ring.with_member(member).unwrap();
+        assert_eq!(same_ring, new_ring);
+    }
+}
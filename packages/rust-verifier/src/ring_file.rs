@@ -0,0 +1,273 @@
+//! The on-disk format of a ring, in JSON or TOML:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "curve": "SECP256K1",
+//!   "ringId": "<keccak256 of the serialized ring>",
+//!   "createdAt": 1729346400,
+//!   "members": [
+//!     { "publicKey": "02...", "label": "alice", "address": "0x..." }
+//!   ]
+//! }
+//! ```
+//!
+//! Members are compressed hex public keys. They may be listed in any order: the ring is
+//! built in canonical order, so `ringId` only depends on the set of public keys.
+//! `createdAt` is a unix timestamp in seconds. `label` and `address` are optional:
+//! `address` is the EIP-55 checksummed ethereum address of the member, checked on load.
+
+use crate::ring::Ring;
+use crate::utils::curve::Curve;
+use crate::utils::eth_address::{eth_address_bytes, parse_eth_address, to_eth_address};
+use crate::utils::serialize_point::serialize_point;
+use crate::utils::serialize_ring::deserialize_ring;
+use k256::AffinePoint;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The current version of the ring file format
+pub const RING_FILE_VERSION: u32 = 1;
+
+/// A member of a ring file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RingMember {
+    /// The compressed hex public key
+    pub public_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+/// A ring stored on disk, with its metadata
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RingFile {
+    pub version: u32,
+    pub curve: Curve,
+    pub ring_id: String,
+    pub created_at: u64,
+    pub members: Vec<RingMember>,
+}
+
+impl RingFile {
+    /// Creates a ring file from a ring, annotating the members with their ethereum address
+    pub fn new(ring: &Ring) -> RingFile {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        RingFile {
+            version: RING_FILE_VERSION,
+            curve: Curve::Secp256k1,
+            ring_id: ring.ring_id(),
+            created_at,
+            members: ring
+                .iter()
+                .map(|point| RingMember {
+                    public_key: serialize_point(*point),
+                    label: None,
+                    address: Some(to_eth_address(point)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Sets the label of a member
+    pub fn set_label(&mut self, public_key: &AffinePoint, label: &str) -> Result<(), String> {
+        self.member_mut(public_key)?.label = Some(label.to_string());
+        Ok(())
+    }
+
+    /// Returns the member with the given label
+    pub fn member_by_label(&self, label: &str) -> Option<&RingMember> {
+        self.members
+            .iter()
+            .find(|member| member.label.as_deref() == Some(label))
+    }
+
+    /// Validates the file and returns its ring.
+    ///
+    /// Checks the version, the curve (only secp256k1 ring files are supported), the public keys
+    /// (valid, without duplicates), the addresses and the ring ID.
+    pub fn ring(&self) -> Result<Ring, String> {
+        if self.version != RING_FILE_VERSION {
+            return Err(format!("Unsupported ring file version: {}", self.version));
+        }
+        if self.curve != Curve::Secp256k1 {
+            return Err(format!(
+                "Expected a {} ring file, got a {} ring file",
+                Curve::Secp256k1.name(),
+                self.curve.name()
+            ));
+        }
+
+        let public_keys: Vec<String> = self
+            .members
+            .iter()
+            .map(|member| member.public_key.clone())
+            .collect();
+        let points = deserialize_ring(&public_keys)?;
+
+        for (member, point) in self.members.iter().zip(&points) {
+            if let Some(address) = &member.address {
+                if parse_eth_address(address)? != eth_address_bytes(point) {
+                    return Err(format!(
+                        "The address {} does not match the public key {}",
+                        address, member.public_key
+                    ));
+                }
+            }
+        }
+
+        let ring = Ring::new(points)?;
+
+        if ring.ring_id() != self.ring_id {
+            return Err(format!(
+                "Invalid ring ID: expected {}, got {}",
+                ring.ring_id(),
+                self.ring_id
+            ));
+        }
+
+        Ok(ring)
+    }
+
+    /// Parses and validates a JSON ring file
+    pub fn from_json(json: &str) -> Result<RingFile, String> {
+        let ring_file: RingFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON ring file: {}", e))?;
+        ring_file.ring()?;
+
+        Ok(ring_file)
+    }
+
+    /// Converts the ring file to a pretty-printed JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parses and validates a TOML ring file
+    pub fn from_toml(toml: &str) -> Result<RingFile, String> {
+        let ring_file: RingFile =
+            toml::from_str(toml).map_err(|e| format!("Invalid TOML ring file: {}", e))?;
+        ring_file.ring()?;
+
+        Ok(ring_file)
+    }
+
+    /// Converts the ring file to a TOML string
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// Loads and validates a ring file. Files with a `.toml` extension are parsed as TOML,
+    /// any other file as JSON.
+    pub fn load(path: impl AsRef<Path>) -> Result<RingFile, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        if is_toml(path) {
+            RingFile::from_toml(&content)
+        } else {
+            RingFile::from_json(&content)
+        }
+    }
+
+    /// Writes the ring file. Files with a `.toml` extension are written as TOML,
+    /// any other file as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let content = if is_toml(path) {
+            self.to_toml()
+        } else {
+            self.to_json()
+        };
+
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn member_mut(&mut self, public_key: &AffinePoint) -> Result<&mut RingMember, String> {
+        let serialized = serialize_point(*public_key);
+        self.members
+            .iter_mut()
+            .find(|member| member.public_key == serialized)
+            .ok_or_else(|| "The public key is not a member of the ring".to_string())
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().and_then(|extension| extension.to_str()) == Some("toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{get_sorted_ring, TestDir};
+
+    #[test]
+    fn test_ring_file_round_trip() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let mut ring_file = RingFile::new(&ring);
+        ring_file.set_label(&ring[0], "alice").unwrap();
+        assert!(ring_file
+            .set_label(&get_sorted_ring(&[42])[0], "mallory")
+            .is_err());
+
+        let from_json = RingFile::from_json(&ring_file.to_json()).expect("invalid JSON");
+        assert_eq!(from_json, ring_file);
+        assert_eq!(from_json.ring(), Ok(ring.clone()));
+        assert_eq!(
+            from_json.member_by_label("alice").unwrap().public_key,
+            serialize_point(ring[0])
+        );
+        assert_eq!(from_json.members[0].address, Some(to_eth_address(&ring[0])));
+
+        let toml = ring_file.to_toml();
+        assert!(toml.contains("[[members]]"));
+        assert_eq!(RingFile::from_toml(&toml), Ok(ring_file.clone()));
+
+        let dir = TestDir::new("test_ring_file_round_trip");
+        for file_name in ["ring.json", "ring.toml"] {
+            let path = dir.path().join(file_name);
+            ring_file.save(&path).unwrap();
+            assert_eq!(RingFile::load(&path), Ok(ring_file.clone()));
+        }
+    }
+
+    #[test]
+    fn test_invalid_ring_files() {
+        let ring_file = RingFile::new(&get_sorted_ring(&[2, 3, 4]));
+
+        let mut wrong_id = ring_file.clone();
+        wrong_id.ring_id = RingFile::new(&get_sorted_ring(&[2, 3])).ring_id;
+        assert!(wrong_id.ring().is_err());
+
+        let mut duplicates = ring_file.clone();
+        duplicates.members.push(duplicates.members[0].clone());
+        assert!(duplicates.ring().is_err());
+
+        let mut wrong_version = ring_file.clone();
+        wrong_version.version = 2;
+        assert!(wrong_version.ring().is_err());
+
+        let mut wrong_curve = ring_file.clone();
+        wrong_curve.curve = Curve::P256;
+        assert!(wrong_curve.ring().is_err());
+        assert!(RingFile::from_json(&wrong_curve.to_json()).is_err());
+
+        let mut wrong_address = ring_file.clone();
+        wrong_address.members[0].address = wrong_address.members[1].address.clone();
+        assert!(wrong_address.ring().is_err());
+
+        let mut invalid_key = ring_file;
+        invalid_key.members[0].public_key = "02abcd".to_string();
+        assert!(RingFile::from_json(&invalid_key.to_json()).is_err());
+    }
+}
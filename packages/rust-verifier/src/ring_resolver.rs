@@ -0,0 +1,319 @@
+use crate::lsag_verifier::{verify_lsag, Lsag};
+use crate::ring::Ring;
+use crate::ring_file::RingFile;
+use crate::utils::base64_json::decode_base64_json;
+use crate::utils::curve::Curve;
+use crate::utils::linkability_flag::parse_linkability_flag;
+use crate::utils::scalar_from_hex::scalar_from_hex;
+use crate::utils::scalar_to_string::scalar_to_trimmed_string;
+use crate::utils::serialize_point::{deserialize_point, serialize_point};
+use crate::utils::signature_config::SignatureConfig;
+use base64::engine::general_purpose;
+use base64::Engine;
+use k256::{AffinePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Resolves a ring from its ring ID (see `Ring::ring_id`)
+pub trait RingResolver {
+    fn resolve(&self, ring_id: &str) -> Result<Ring, String>;
+}
+
+/// A resolver holding its rings in memory
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryRingResolver {
+    rings: HashMap<String, Ring>,
+}
+
+impl InMemoryRingResolver {
+    pub fn new() -> InMemoryRingResolver {
+        InMemoryRingResolver::default()
+    }
+
+    /// Adds a ring to the resolver and returns its ring ID
+    pub fn insert(&mut self, ring: Ring) -> String {
+        let ring_id = ring.ring_id();
+        self.rings.insert(ring_id.clone(), ring);
+        ring_id
+    }
+}
+
+impl RingResolver for InMemoryRingResolver {
+    fn resolve(&self, ring_id: &str) -> Result<Ring, String> {
+        self.rings
+            .get(ring_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown ring: {}", ring_id))
+    }
+}
+
+/// A resolver loading ring files named `<ring_id>.json` or `<ring_id>.toml` from a directory
+#[derive(Clone, Debug)]
+pub struct DirectoryRingResolver {
+    directory: PathBuf,
+}
+
+impl DirectoryRingResolver {
+    pub fn new(directory: impl AsRef<Path>) -> DirectoryRingResolver {
+        DirectoryRingResolver {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Writes a ring file to the directory as `<ring_id>.json` and returns its path
+    pub fn store(&self, ring_file: &RingFile) -> Result<PathBuf, String> {
+        ring_file.ring()?;
+        check_ring_id(&ring_file.ring_id)?;
+
+        let path = self.directory.join(format!("{}.json", ring_file.ring_id));
+        ring_file.save(&path)?;
+        Ok(path)
+    }
+}
+
+impl RingResolver for DirectoryRingResolver {
+    fn resolve(&self, ring_id: &str) -> Result<Ring, String> {
+        // the ring id is used as a file name
+        check_ring_id(ring_id)?;
+
+        let path = ["json", "toml"]
+            .iter()
+            .map(|extension| self.directory.join(format!("{}.{}", ring_id, extension)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("Unknown ring: {}", ring_id))?;
+
+        let ring_file = RingFile::load(path)?;
+        if ring_file.ring_id != ring_id {
+            return Err(format!("Unknown ring: {}", ring_id));
+        }
+        ring_file.ring()
+    }
+}
+
+fn check_ring_id(ring_id: &str) -> Result<(), String> {
+    if ring_id.len() != 64
+        || !ring_id
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    {
+        return Err("A ring ID must be a 64-character lowercase hex string".to_string());
+    }
+    Ok(())
+}
+
+/// Define a struct that matches the structure of the JSON string of a LSAG referencing its ring
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StringifiedRingRefLsag {
+    pub message: String,
+    pub ringId: String,
+    pub c: String,
+    pub responses: Vec<String>,
+    pub curve: String,
+    pub keyImage: String,
+    pub linkabilityFlag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<SignatureConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evmWitnesses: Option<Vec<String>>,
+}
+
+/// A LSAG signature carrying the ID of its ring instead of the ring itself
+#[derive(Clone, Debug, PartialEq)]
+pub struct RingRefLsag {
+    pub ring_id: String,
+    pub message: String,
+    pub c0: Scalar,
+    pub responses: Vec<Scalar>,
+    pub key_image: AffinePoint,
+    pub linkability_flag: Option<String>,
+    pub curve: Curve,
+    pub config: Option<SignatureConfig>,
+    pub evm_witnesses: Option<Vec<String>>,
+}
+
+impl RingRefLsag {
+    /// Resolves the ring and returns the full signature
+    pub fn resolve(self, resolver: &dyn RingResolver) -> Result<Lsag, String> {
+        let ring = resolver.resolve(&self.ring_id)?;
+        if ring.ring_id() != self.ring_id {
+            return Err("The resolved ring does not match the ring ID".to_string());
+        }
+        if ring.len() != self.responses.len() {
+            return Err("Ring and responses must have the same length".to_string());
+        }
+
+        Ok(Lsag {
+            ring: ring.into_points(),
+            message: self.message,
+            c0: self.c0,
+            responses: self.responses,
+            key_image: self.key_image,
+            linkability_flag: self.linkability_flag,
+            config: self.config,
+            evm_witnesses: self.evm_witnesses,
+        })
+    }
+
+    /// Parses a signature from its JSON string
+    pub fn from_json(json: &str) -> Result<RingRefLsag, String> {
+        let stringified: StringifiedRingRefLsag =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        RingRefLsag::try_from(stringified)
+    }
+
+    /// Converts the signature to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedRingRefLsag::from(self)).unwrap()
+    }
+
+    /// Parses a signature from its base64 encoded JSON string
+    pub fn from_base64(b64_signature: &str) -> Result<RingRefLsag, String> {
+        RingRefLsag::from_json(&decode_base64_json(b64_signature)?)
+    }
+
+    /// Converts the signature to a base64 encoded JSON string
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.to_json())
+    }
+}
+
+impl TryFrom<&Lsag> for RingRefLsag {
+    type Error = String;
+
+    /// Replaces the ring of a signature by its ring ID. The ring must be in canonical order.
+    fn try_from(signature: &Lsag) -> Result<RingRefLsag, String> {
+        let ring = Ring::from_sorted(signature.ring.clone())?;
+
+        Ok(RingRefLsag {
+            ring_id: ring.ring_id(),
+            message: signature.message.clone(),
+            c0: signature.c0,
+            responses: signature.responses.clone(),
+            key_image: signature.key_image,
+            linkability_flag: signature.linkability_flag.clone(),
+            curve: Curve::Secp256k1,
+            config: signature.config.clone(),
+            evm_witnesses: signature.evm_witnesses.clone(),
+        })
+    }
+}
+
+impl TryFrom<StringifiedRingRefLsag> for RingRefLsag {
+    type Error = String;
+
+    fn try_from(json: StringifiedRingRefLsag) -> Result<RingRefLsag, String> {
+        check_ring_id(&json.ringId)?;
+        if json.responses.is_empty() {
+            return Err("Responses cannot be empty".to_string());
+        }
+
+        let curve = Curve::from_json_string(&json.curve)?;
+        if curve != Curve::Secp256k1 {
+            return Err(format!("Unsupported curve: {}", curve.name()));
+        }
+
+        let responses = json
+            .responses
+            .iter()
+            .map(|response| scalar_from_hex(response))
+            .collect::<Result<Vec<Scalar>, String>>()?;
+
+        Ok(RingRefLsag {
+            ring_id: json.ringId,
+            message: json.message,
+            c0: scalar_from_hex(&json.c)?,
+            responses,
+            key_image: deserialize_point(json.keyImage)?,
+            linkability_flag: parse_linkability_flag(json.linkabilityFlag),
+            curve,
+            config: json.config,
+            evm_witnesses: json.evmWitnesses,
+        })
+    }
+}
+
+impl From<&RingRefLsag> for StringifiedRingRefLsag {
+    fn from(signature: &RingRefLsag) -> StringifiedRingRefLsag {
+        StringifiedRingRefLsag {
+            message: signature.message.clone(),
+            ringId: signature.ring_id.clone(),
+            c: scalar_to_trimmed_string(&signature.c0),
+            responses: signature
+                .responses
+                .iter()
+                .map(scalar_to_trimmed_string)
+                .collect(),
+            curve: signature.curve.to_json_string(),
+            keyImage: serialize_point(signature.key_image),
+            linkabilityFlag: signature.linkability_flag.clone().unwrap_or_default(),
+            config: signature.config.clone(),
+            evmWitnesses: signature.evm_witnesses.clone(),
+        }
+    }
+}
+
+/// Resolves the ring of a signature and verifies it.
+/// Returns `false` if the ring cannot be resolved.
+pub fn verify_lsag_with_resolver(signature: RingRefLsag, resolver: &dyn RingResolver) -> bool {
+    match signature.resolve(resolver) {
+        Ok(signature) => verify_lsag(signature),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsag_signer::sign_lsag;
+    use crate::utils::test_utils::{get_sorted_ring, TestDir};
+
+    fn sign(ring: &Ring) -> RingRefLsag {
+        let signature = sign_lsag(
+            ring,
+            Scalar::from(3u64),
+            "message".to_string(),
+            Some("flag".to_string()),
+        )
+        .unwrap();
+
+        RingRefLsag::try_from(&signature).unwrap()
+    }
+
+    #[test]
+    fn test_verify_with_in_memory_resolver() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let signature = sign(&ring);
+
+        let mut resolver = InMemoryRingResolver::new();
+        assert!(!verify_lsag_with_resolver(signature.clone(), &resolver));
+
+        assert_eq!(resolver.insert(ring), signature.ring_id);
+        let parsed = RingRefLsag::from_base64(&signature.to_base64()).unwrap();
+        assert_eq!(parsed, signature);
+        assert!(verify_lsag_with_resolver(parsed, &resolver));
+
+        let mut tampered = signature;
+        tampered.message = "other message".to_string();
+        assert!(!verify_lsag_with_resolver(tampered, &resolver));
+    }
+
+    #[test]
+    fn test_verify_with_directory_resolver() {
+        let ring = get_sorted_ring(&[2, 3, 4]);
+        let signature = sign(&ring);
+
+        let directory = TestDir::new("test_verify_with_directory_resolver");
+        let resolver = DirectoryRingResolver::new(directory.path());
+
+        resolver.store(&RingFile::new(&ring)).unwrap();
+        assert!(verify_lsag_with_resolver(signature, &resolver));
+        assert!(resolver.resolve("../../etc/passwd").is_err());
+        assert!(resolver.resolve(&ring.ring_id().to_uppercase()).is_err());
+        assert!(resolver
+            .resolve(&get_sorted_ring(&[2, 3]).ring_id())
+            .is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::lsag_signer::insert_generic_signer;
+use crate::sag_verifier::{compute_c, compute_c_from_point, GenericSag, Params, Sag};
+use crate::utils::curve::{Curve, RingCurve};
+use crate::utils::keccak256::keccak_256;
+use crate::utils::signature_config::{HashFunction, SignatureConfig};
+use elliptic_curve::Field;
+use k256::Secp256k1;
+
+/// Signs a message using a SAG ring signature, as `RingSignature.sign()` does in `sag-ts`.
+///
+/// The ring must be sorted by x ascending coordinate (and y ascending if x's are equal).
+/// If the ring does not contain the signer public key, it is inserted at its sorted position.
+pub fn sign_sag(
+    ring: &[k256::AffinePoint],
+    signer_private_key: k256::Scalar,
+    message: String,
+    config: Option<SignatureConfig>,
+) -> Result<Sag, String> {
+    sign_generic_sag::<Secp256k1>(ring, signer_private_key, message, config)
+}
+
+/// Signs a message using a SAG ring signature on any supported curve (see `sign_sag`).
+/// EVM compatible signatures can only be computed on secp256k1.
+pub fn sign_generic_sag<C: RingCurve>(
+    ring: &[C::Point],
+    signer_private_key: C::Scalar,
+    message: String,
+    config: Option<SignatureConfig>,
+) -> Result<GenericSag<C>, String> {
+    if bool::from(signer_private_key.is_zero()) {
+        return Err("Signer private key cannot be 0".to_string());
+    }
+    if let Some(HashFunction::Sha256 | HashFunction::Sha512) =
+        config.as_ref().and_then(|config| config.hash)
+    {
+        return Err("Unsupported hash function".to_string());
+    }
+    let evm_compatibility = config
+        .as_ref()
+        .and_then(|config| config.evm_compatibility)
+        .unwrap_or(false);
+    if evm_compatibility && C::CURVE != Curve::Secp256k1 {
+        return Err("EVM compatibility is only supported on secp256k1".to_string());
+    }
+
+    let (ring, signer_index) = insert_generic_signer::<C>(ring, &signer_private_key)?;
+
+    let message_digest = keccak_256(std::slice::from_ref(&message));
+    let serialized_ring = ring.serialized().to_string();
+
+    let alpha = C::random_scalar();
+
+    // generate random responses for every public key in the ring
+    let mut responses: Vec<C::Scalar> = ring.iter().map(|_| C::random_scalar()).collect();
+
+    // contains all the cees from 0 to ring.len() - 1 (0, 1, ..., pi, ..., ring.len() - 1)
+    let mut cees = vec![C::Scalar::ZERO; ring.len()];
+
+    // compute cpi+1
+    let first_index = (signer_index + 1) % ring.len();
+    cees[first_index] = compute_c_from_point::<C>(
+        &serialized_ring,
+        &message_digest,
+        C::mul_generator(&alpha),
+        first_index,
+        evm_compatibility,
+    );
+
+    // compute the c values from pi+2 to pi (included)
+    for i in signer_index + 2..signer_index + ring.len() + 1 {
+        let index = i % ring.len();
+        let previous_index = (index + ring.len() - 1) % ring.len();
+
+        let params = Params::<C> {
+            index,
+            previous_r: responses[previous_index],
+            previous_c: cees[previous_index],
+            previous_index,
+        };
+
+        cees[index] = compute_c(
+            &ring,
+            serialized_ring.clone(),
+            message_digest.clone(),
+            &params,
+            evm_compatibility,
+        );
+    }
+
+    // compute the signer response
+    responses[signer_index] = alpha - cees[signer_index] * signer_private_key;
+
+    Ok(GenericSag {
+        ring: ring.into_points(),
+        message,
+        c0: cees[0],
+        responses,
+        config,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign_generic_sag, sign_sag};
+    use crate::ring::GenericRing;
+    use crate::sag_verifier::{verify_sag, P256Sag, RistrettoSag};
+    use crate::utils::curve::{RingCurve, Ristretto255};
+    use crate::utils::signature_config::SignatureConfig;
+    use crate::utils::test_utils::get_sorted_ring;
+    use k256::Scalar;
+    use p256::NistP256;
+
+    #[test]
+    fn test_sign_sag() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+
+        let signature = sign_sag(&ring, Scalar::from(42u64), "message".to_string(), None)
+            .expect("failed to sign");
+
+        assert_eq!(signature.ring.len(), 5);
+        assert!(verify_sag(signature.clone()));
+
+        let mut tampered = signature;
+        tampered.message = "another message".to_string();
+        assert!(!verify_sag(tampered));
+    }
+
+    #[test]
+    fn test_sign_evm_compatible_sag() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let config = SignatureConfig {
+            evm_compatibility: Some(true),
+            hash: None,
+        };
+
+        let signature = sign_sag(
+            &ring,
+            Scalar::from(3u64),
+            "message".to_string(),
+            Some(config),
+        )
+        .expect("failed to sign");
+
+        assert!(signature.is_evm_compatible());
+        assert!(verify_sag(signature));
+    }
+
+    #[test]
+    fn test_sign_p256_sag() {
+        let ring: Vec<p256::AffinePoint> = [2u64, 3, 4]
+            .iter()
+            .map(|k| (p256::AffinePoint::GENERATOR * p256::Scalar::from(*k)).to_affine())
+            .collect();
+        let ring = crate::ring::GenericRing::<NistP256>::new(ring).unwrap();
+
+        let signature = sign_generic_sag::<NistP256>(
+            &ring,
+            p256::Scalar::from(7u64),
+            "message".to_string(),
+            None,
+        )
+        .expect("failed to sign");
+        assert!(verify_sag(signature.clone()));
+        assert_eq!(P256Sag::from_json(&signature.to_json()), Ok(signature));
+
+        let evm_config = SignatureConfig {
+            evm_compatibility: Some(true),
+            hash: None,
+        };
+        assert!(sign_generic_sag::<NistP256>(
+            &ring,
+            p256::Scalar::from(7u64),
+            "message".to_string(),
+            Some(evm_config)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sign_ristretto255_sag() {
+        let ring = GenericRing::<Ristretto255>::new(
+            [2u64, 3, 4]
+                .iter()
+                .map(|k| Ristretto255::mul_generator(&curve25519_dalek::Scalar::from(*k)))
+                .collect(),
+        )
+        .unwrap();
+
+        let signature = sign_generic_sag::<Ristretto255>(
+            &ring,
+            curve25519_dalek::Scalar::from(7u64),
+            "message".to_string(),
+            None,
+        )
+        .expect("failed to sign");
+        assert!(verify_sag(signature.clone()));
+        assert_eq!(RistrettoSag::from_json(&signature.to_json()), Ok(signature));
+    }
+}
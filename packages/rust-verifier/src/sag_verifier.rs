@@ -0,0 +1,329 @@
+use crate::ring::Ring;
+use crate::utils::base64_json::decode_base64_json;
+use crate::utils::curve::{Curve, RingCurve, Ristretto255};
+use crate::utils::hex_to_decimal::hex_to_decimal;
+use crate::utils::keccak256::keccak_256;
+use crate::utils::signature_config::{HashFunction, SignatureConfig};
+use base64::engine::general_purpose;
+use base64::Engine;
+use k256::Secp256k1;
+use p256::NistP256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Define a struct that matches the structure of the JSON string SAG
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StringifiedSag {
+    pub message: String,
+    pub ring: Vec<String>,
+    pub c: String,
+    pub responses: Vec<String>,
+    pub curve: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<SignatureConfig>,
+}
+/// A struct to represent a SAG signature on any supported curve
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericSag<C: RingCurve> {
+    pub ring: Vec<C::Point>,
+    pub message: String,
+    pub c0: C::Scalar,
+    pub responses: Vec<C::Scalar>,
+    pub config: Option<SignatureConfig>,
+}
+
+/// A SAG signature on secp256k1, as produced by `sag-ts`
+pub type Sag = GenericSag<Secp256k1>;
+
+/// A SAG signature on NIST P-256
+pub type P256Sag = GenericSag<NistP256>;
+
+/// A SAG signature on ristretto255
+pub type RistrettoSag = GenericSag<Ristretto255>;
+
+impl<C: RingCurve> GenericSag<C> {
+    /// Parses a SAG signature from the JSON string produced by `toJsonString()` in `sag-ts`
+    pub fn from_json(json: &str) -> Result<GenericSag<C>, String> {
+        let stringified: StringifiedSag =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        GenericSag::try_from(stringified)
+    }
+
+    /// Converts the signature to a JSON string, byte-identical to `toJsonString()` in `sag-ts`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedSag::from(self)).unwrap()
+    }
+
+    /// Parses a SAG signature from the base64 string produced by `toBase64()` in `sag-ts`
+    pub fn from_base64(b64_signature: &str) -> Result<GenericSag<C>, String> {
+        GenericSag::from_json(&decode_base64_json(b64_signature)?)
+    }
+
+    /// Converts the signature to a base64 string, byte-identical to `toBase64()` in `sag-ts`
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.to_json())
+    }
+
+    /// Returns true if the signature was computed for the SAG EVM verifier contract
+    pub fn is_evm_compatible(&self) -> bool {
+        self.config
+            .as_ref()
+            .and_then(|config| config.evm_compatibility)
+            .unwrap_or(false)
+    }
+}
+
+impl<C: RingCurve> TryFrom<StringifiedSag> for GenericSag<C> {
+    type Error = String;
+
+    fn try_from(json: StringifiedSag) -> Result<GenericSag<C>, String> {
+        if json.ring.is_empty() {
+            return Err("Ring cannot be empty".to_string());
+        }
+        if json.ring.len() != json.responses.len() {
+            return Err("Ring and responses must have the same length".to_string());
+        }
+
+        let curve = Curve::from_json_string(&json.curve)?;
+        if curve != C::CURVE {
+            return Err(format!(
+                "Expected a {} signature, got a {} signature",
+                C::CURVE.name(),
+                curve.name()
+            ));
+        }
+
+        let ring = json
+            .ring
+            .iter()
+            .map(|point| C::deserialize_point(point))
+            .collect::<Result<Vec<C::Point>, String>>()?;
+        let c0 = C::scalar_from_hex(&json.c)?;
+        let responses = json
+            .responses
+            .iter()
+            .map(|response| C::scalar_from_hex(response))
+            .collect::<Result<Vec<C::Scalar>, String>>()?;
+
+        Ok(GenericSag {
+            ring,
+            message: json.message,
+            c0,
+            responses,
+            config: json.config,
+        })
+    }
+}
+
+impl<C: RingCurve> From<&GenericSag<C>> for StringifiedSag {
+    fn from(signature: &GenericSag<C>) -> StringifiedSag {
+        StringifiedSag {
+            message: signature.message.clone(),
+            ring: signature.ring.iter().map(C::serialize_point).collect(),
+            c: C::scalar_to_hex(&signature.c0),
+            responses: signature.responses.iter().map(C::scalar_to_hex).collect(),
+            curve: C::CURVE.to_json_string(),
+            config: signature.config.clone(),
+        }
+    }
+}
+/// Parameters required for the compute_c function
+pub struct Params<C: RingCurve> {
+    pub index: usize,
+    pub previous_r: C::Scalar,
+    pub previous_c: C::Scalar,
+    pub previous_index: usize,
+}
+
+/// Computes the 'cee' value based on the provided parameters
+///
+/// If `evm_compatibility` is set, the hash is computed as the SAG EVM verifier contract does:
+/// the ring is not hashed, the message digest is only hashed for c1 and the point is
+/// replaced by its ethereum address.
+pub fn compute_c<C: RingCurve>(
+    ring: &[C::Point],
+    serialized_ring: String,
+    message_digest: String,
+    params: &Params<C>,
+    evm_compatibility: bool,
+) -> C::Scalar {
+    let point = C::lincomb(
+        &C::generator(),
+        &params.previous_r,
+        &ring[params.previous_index],
+        &params.previous_c,
+    );
+
+    compute_c_from_point::<C>(
+        &serialized_ring,
+        &message_digest,
+        point,
+        params.index,
+        evm_compatibility,
+    )
+}
+
+/// Computes the 'cee' value of index `index` from `r*G + c*K` (or `alpha*G` for the signer)
+pub fn compute_c_from_point<C: RingCurve>(
+    serialized_ring: &str,
+    message_digest: &str,
+    point: C::Point,
+    index: usize,
+    evm_compatibility: bool,
+) -> C::Scalar {
+    if evm_compatibility {
+        // bigints are hashed as 32 bytes big endian integers
+        let mut hash_content = Vec::new();
+        if index == 1 {
+            hash_content.extend_from_slice(&hex::decode(message_digest).unwrap());
+        }
+        hash_content.extend_from_slice(&[0u8; 12]);
+        hash_content.extend_from_slice(&C::eth_address(&point).unwrap_or_default());
+
+        let hash = Keccak256::digest(hash_content);
+
+        return C::scalar_from_hash(&hash);
+    }
+
+    let hash_content = "".to_string()
+        + serialized_ring
+        + &hex_to_decimal(message_digest).unwrap()
+        + &C::serialize_point(&point);
+
+    let hash = Keccak256::digest(hash_content.as_bytes());
+
+    C::scalar_from_hash(&hash)
+}
+
+/// Verify a base64 encoded SAG signature.
+/// Converts a base64 encoded SAG signature and verifies it.
+pub fn verify_b64_sag(b64_signature: String) -> bool {
+    let sag_signature = match Sag::from_base64(&b64_signature) {
+        Ok(signature) => signature,
+        Err(_) => return false, // Return false if deserialization fails
+    };
+
+    // Return the result of the verification
+    verify_sag(sag_signature)
+}
+
+/// Verifies a ring signature whose ring must be in canonical order (see `Ring`).
+/// Returns `false` if the ring is not canonical, even if the signature itself is valid.
+pub fn verify_sag_canonical(signature: Sag) -> bool {
+    Ring::is_canonical(&signature.ring) && verify_sag(signature)
+}
+
+/// Verifies a ring signature.
+/// Returns `true` if the signature is valid, `false` otherwise, including when its ring is
+/// empty or does not match its responses.
+pub fn verify_sag<C: RingCurve>(signature: GenericSag<C>) -> bool {
+    // Ensure that the ring is not empty and matches the responses
+    if signature.ring.is_empty() || signature.ring.len() != signature.responses.len() {
+        return false;
+    }
+
+    // Only keccak256 is supported for now
+    if let Some(HashFunction::Sha256 | HashFunction::Sha512) =
+        signature.config.as_ref().and_then(|config| config.hash)
+    {
+        return false;
+    }
+    // EVM compatibility is only supported on secp256k1
+    let evm_compatibility = signature.is_evm_compatible();
+    if evm_compatibility && C::CURVE != Curve::Secp256k1 {
+        return false;
+    }
+
+    let message_digest = keccak_256(&[signature.message]);
+
+    let serialized_ring: String = signature.ring.iter().map(C::serialize_point).collect();
+
+    // Initialize last_computed_c with c0
+    let mut last_computed_c = signature.c0;
+
+    // Compute the c values: c1', c2', ..., cn', c0'
+    for i in 0..signature.ring.len() {
+        let params = Params::<C> {
+            index: (i + 1) % signature.ring.len(),
+            previous_r: signature.responses[i],
+            previous_c: last_computed_c,
+            previous_index: i,
+        };
+
+        last_computed_c = compute_c(
+            &signature.ring,
+            serialized_ring.clone(),
+            message_digest.clone(),
+            &params,
+            evm_compatibility,
+        );
+    }
+
+    // Return true if c0 == c0'
+    signature.c0 == last_computed_c
+}
+#[cfg(test)]
+mod tests {
+    use super::{verify_b64_sag, verify_sag, RistrettoSag, Sag};
+    use serde_json::Value;
+    use std::fs;
+
+    fn load_ts_signatures() -> Value {
+        // Load the JSON signature data from `../../sag-ts/test/data/jsonSignatures.json`
+        let data = fs::read_to_string("../sag-ts/test/data/jsonSignatures.json")
+            .expect("Unable to read json file");
+
+        serde_json::from_str(&data).expect("JSON was not well-formatted")
+    }
+
+    /// Test the `verify_sag` function using the evm compatible signature from the `sag` package.
+    #[test]
+    fn test_verify_evm_compatible_sag_from_ts_package() {
+        let json_data = load_ts_signatures();
+
+        let signature =
+            Sag::from_json(&json_data["valid"].to_string()).expect("failed to parse the signature");
+
+        assert!(signature.is_evm_compatible());
+        assert!(verify_sag(signature.clone()));
+
+        let mut tampered = signature;
+        tampered.message = "Hello world ?".to_string();
+        assert!(!verify_sag(tampered));
+    }
+
+    /// Test the base64 round trip using the base64 signature from the `sag` package.
+    #[test]
+    fn test_base64_round_trip() {
+        let json_data = load_ts_signatures();
+        let b64_signature = json_data["validBase64Sig"].as_str().unwrap();
+
+        // one of the responses has an odd length once its leading zeros are removed
+        let signature = Sag::from_base64(b64_signature).expect("failed to parse the signature");
+        assert_eq!(signature.config, None);
+        assert_eq!(signature.to_base64(), b64_signature);
+
+        let b64_evm_signature = Sag::from_json(&json_data["valid"].to_string())
+            .unwrap()
+            .to_base64();
+        assert!(verify_b64_sag(b64_evm_signature));
+        assert!(!verify_b64_sag("not a base64 signature".to_string()));
+    }
+
+    /// Test the `verify_sag` function with a ristretto255 signature computed by this crate,
+    /// with the private keys 2, 3, 4 and 7 (the signer)
+    #[test]
+    fn test_verify_ristretto255_sag() {
+        let json = r#"{"message":"message","ring":["44f53520926ec81fbd5a387845beb7df85a96a24ece18738bdcfa6a7822a176d","6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919","94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259","da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57"],"c":"9af1af1ecc6f1aa8ea406ed5b149c9a25e4763f7830c82c135de7b14c30b62a","responses":["4544a64a9cab571845d6457f3287ab1fcac6f132d63cf407c02b550eb8a77cd","611aa6a7347d900169247e7fa96ed39e1aaa5000065859644d5201a8b65811d","8fc1f2b2e322b6eafa567641d08704685964968c7cc47823863b3814c1e51f9","2330557834fd0b88c59b9652a955412c147fc90bf685e89ced185a4332138bf"],"curve":"{\"curve\":\"RISTRETTO255\"}"}"#;
+
+        let signature = RistrettoSag::from_json(json).expect("failed to parse the signature");
+        assert_eq!(signature.to_json(), json);
+        assert!(verify_sag(signature.clone()));
+        assert!(Sag::from_json(json).is_err());
+
+        let mut tampered = signature;
+        tampered.message = "another message".to_string();
+        assert!(!verify_sag(tampered));
+    }
+}
@@ -0,0 +1,489 @@
+//! Ring members from OpenSSH public keys, as listed in `authorized_keys` files or in the
+//! `https://github.com/<user>.keys` files.
+//!
+//! Supported key types are `ssh-ed25519` (on the `Ed25519` curve) and `ecdsa-sha2-nistp256`
+//! (on `NistP256`). Other key types (e.g. `ssh-rsa`) cannot be ring members and are skipped.
+//! A ring only holds keys of one curve, so a team with both key types signs with the ring of
+//! its own key type.
+//!
+//! Ring signatures are exchanged as armored text, like `ssh-keygen -Y sign` does:
+//!
+//! ```text
+//! -----BEGIN SSH RING SIGNATURE-----
+//! <base64 of the binary LSAG signature, wrapped at 70 characters>
+//! -----END SSH RING SIGNATURE-----
+//! ```
+use crate::lsag_signer::sign_generic_lsag;
+use crate::lsag_verifier::{verify_lsag, GenericLsag};
+use crate::ring::GenericRing;
+use crate::utils::curve::{Ed25519, RingCurve};
+use base64::engine::general_purpose;
+use base64::Engine;
+use curve25519_dalek::EdwardsPoint;
+use elliptic_curve::sec1::ToEncodedPoint;
+use p256::NistP256;
+
+/// The first line of an armored signature
+pub const ARMOR_BEGIN: &str = "-----BEGIN SSH RING SIGNATURE-----";
+/// The last line of an armored signature
+pub const ARMOR_END: &str = "-----END SSH RING SIGNATURE-----";
+/// The length of the base64 lines of an armored signature
+const ARMOR_LINE_LENGTH: usize = 70;
+
+const KEY_TYPE_ED25519: &str = "ssh-ed25519";
+const KEY_TYPE_ECDSA_P256: &str = "ecdsa-sha2-nistp256";
+
+/// The key of a ring member, on its own curve
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SshKey {
+    Ed25519(EdwardsPoint),
+    EcdsaP256(p256::AffinePoint),
+}
+
+/// An OpenSSH public key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshPublicKey {
+    pub key: SshKey,
+    pub comment: Option<String>,
+}
+
+impl SshPublicKey {
+    /// Parses a public key line (`<type> <base64 blob> [comment]`).
+    /// `authorized_keys` options before the key type are ignored.
+    pub fn parse(line: &str) -> Result<SshPublicKey, String> {
+        let (key_type, rest) = split_key_type(line)?;
+        if !is_supported_key_type(key_type) {
+            return Err(format!("Unsupported SSH key type: {}", key_type));
+        }
+
+        let mut fields = rest.split_whitespace();
+        let blob = fields
+            .next()
+            .ok_or_else(|| "Missing SSH key data".to_string())?;
+        let blob = general_purpose::STANDARD
+            .decode(blob)
+            .map_err(|e| format!("Invalid base64 string: {}", e))?;
+
+        let key = parse_key_blob(key_type, &blob)?;
+        let comment = fields.collect::<Vec<&str>>().join(" ");
+
+        Ok(SshPublicKey {
+            key,
+            comment: (!comment.is_empty()).then_some(comment),
+        })
+    }
+
+    /// Converts the key to its OpenSSH public key line
+    pub fn to_openssh(&self) -> String {
+        let (key_type, blob) = match &self.key {
+            SshKey::Ed25519(point) => {
+                let mut blob = encode_string(KEY_TYPE_ED25519.as_bytes());
+                blob.extend(encode_string(point.compress().as_bytes()));
+                (KEY_TYPE_ED25519, blob)
+            }
+            SshKey::EcdsaP256(point) => {
+                let mut blob = encode_string(KEY_TYPE_ECDSA_P256.as_bytes());
+                blob.extend(encode_string(b"nistp256"));
+                blob.extend(encode_string(point.to_encoded_point(false).as_bytes()));
+                (KEY_TYPE_ECDSA_P256, blob)
+            }
+        };
+
+        let line = format!("{} {}", key_type, general_purpose::STANDARD.encode(blob));
+        match &self.comment {
+            Some(comment) => format!("{} {}", line, comment),
+            None => line,
+        }
+    }
+}
+
+/// The supported keys of an `authorized_keys` or `.keys` file, grouped by curve
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SshKeys {
+    pub ed25519: Vec<SshPublicKey>,
+    pub p256: Vec<SshPublicKey>,
+}
+
+impl SshKeys {
+    /// Returns the P-256 ring members
+    pub fn p256_points(&self) -> Vec<p256::AffinePoint> {
+        self.p256
+            .iter()
+            .filter_map(|public_key| match public_key.key {
+                SshKey::EcdsaP256(point) => Some(point),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the P-256 ring members as a ring, to sign with `sign_generic_lsag`
+    pub fn p256_ring(&self) -> Result<GenericRing<NistP256>, String> {
+        GenericRing::new(self.p256_points())
+    }
+
+    /// Returns the ed25519 ring members
+    pub fn ed25519_points(&self) -> Vec<EdwardsPoint> {
+        self.ed25519
+            .iter()
+            .filter_map(|public_key| match public_key.key {
+                SshKey::Ed25519(point) => Some(point),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the ed25519 ring members as a ring, to sign with `sign_armored`
+    pub fn ed25519_ring(&self) -> Result<GenericRing<Ed25519>, String> {
+        GenericRing::new(self.ed25519_points())
+    }
+}
+
+/// Parses the content of an `authorized_keys` or `.keys` file.
+/// Blank lines, comments and unsupported key types are skipped, invalid keys are rejected.
+pub fn parse_authorized_keys(content: &str) -> Result<SshKeys, String> {
+    let mut keys = SshKeys::default();
+
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = |e: String| format!("Line {}: {}", line_number + 1, e);
+        let (key_type, _) = split_key_type(line).map_err(line_error)?;
+        if !is_supported_key_type(key_type) {
+            continue;
+        }
+
+        let public_key = SshPublicKey::parse(line).map_err(line_error)?;
+        match public_key.key {
+            SshKey::Ed25519(_) => keys.ed25519.push(public_key),
+            SshKey::EcdsaP256(_) => keys.p256.push(public_key),
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Signs a message with a LSAG ring signature and armors it.
+/// The signer public key must be a member of the ring.
+pub fn sign_armored<C: RingCurve>(
+    ring: &GenericRing<C>,
+    signer_private_key: C::Scalar,
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<String, String> {
+    if ring
+        .position(&C::mul_generator(&signer_private_key))
+        .is_none()
+    {
+        return Err("The signer public key is not a member of the ring".to_string());
+    }
+
+    let signature = sign_generic_lsag::<C>(ring, signer_private_key, message, linkability_flag)?;
+    Ok(armor(&signature.to_bytes()?))
+}
+
+/// Verifies an armored LSAG signature of `message` by a member of `ring`.
+/// Returns the signature, whose key image links the signatures of a signer under a flag.
+pub fn verify_armored<C: RingCurve>(
+    armored: &str,
+    ring: &GenericRing<C>,
+    message: &str,
+) -> Result<GenericLsag<C>, String> {
+    let signature = GenericLsag::<C>::from_bytes(&dearmor(armored)?)?;
+
+    if signature.ring != ring.points() {
+        return Err("The signature ring does not match the expected ring".to_string());
+    }
+    if signature.message != message {
+        return Err("The signature does not sign the expected message".to_string());
+    }
+    if !verify_lsag(signature.clone()) {
+        return Err("Invalid ring signature".to_string());
+    }
+
+    Ok(signature)
+}
+
+/// Armors a binary signature
+pub fn armor(signature: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(signature);
+
+    let mut armored = vec![ARMOR_BEGIN.to_string()];
+    armored.extend(
+        encoded
+            .as_bytes()
+            .chunks(ARMOR_LINE_LENGTH)
+            .map(|line| String::from_utf8(line.to_vec()).unwrap()),
+    );
+    armored.push(ARMOR_END.to_string());
+    armored.join("\n") + "\n"
+}
+
+/// Returns the binary signature of an armored signature
+pub fn dearmor(armored: &str) -> Result<Vec<u8>, String> {
+    let lines: Vec<&str> = armored.trim().lines().map(str::trim).collect();
+    if lines.len() < 2 || lines[0] != ARMOR_BEGIN || lines[lines.len() - 1] != ARMOR_END {
+        return Err("Invalid armored signature".to_string());
+    }
+
+    general_purpose::STANDARD
+        .decode(lines[1..lines.len() - 1].concat())
+        .map_err(|e| format!("Invalid base64 string: {}", e))
+}
+
+fn is_supported_key_type(field: &str) -> bool {
+    field == KEY_TYPE_ED25519 || field == KEY_TYPE_ECDSA_P256
+}
+
+/// Whether a field names a key type, supported or not (`ssh-rsa`, `sk-ssh-ed25519@openssh.com`...)
+fn is_key_type(field: &str) -> bool {
+    ["ssh-", "ecdsa-", "sk-"]
+        .iter()
+        .any(|prefix| field.starts_with(prefix))
+}
+
+/// Splits a public key line into its key type and the fields after it,
+/// skipping the `authorized_keys` options that come before the key type
+fn split_key_type(line: &str) -> Result<(&str, &str), String> {
+    let line = line.trim();
+    let first_field = line.split_whitespace().next().unwrap_or_default();
+    let fields = if is_key_type(first_field) {
+        line
+    } else {
+        skip_options(line)?
+    };
+
+    let (key_type, rest) = fields
+        .split_once(char::is_whitespace)
+        .unwrap_or((fields, ""));
+    if !is_key_type(key_type) {
+        return Err("Missing SSH key type".to_string());
+    }
+    Ok((key_type, rest))
+}
+
+/// Skips the options field of an `authorized_keys` line.
+/// Quoted option values (e.g. `command="..."`) may contain spaces and escaped quotes.
+fn skip_options(line: &str) -> Result<&str, String> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if in_quotes && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && c.is_whitespace() {
+            return Ok(line[index..].trim_start());
+        }
+    }
+
+    Err(if in_quotes {
+        "Unterminated quote in the SSH key options".to_string()
+    } else {
+        "Missing SSH key type".to_string()
+    })
+}
+
+/// Parses the SSH wire format of a public key
+fn parse_key_blob(key_type: &str, blob: &[u8]) -> Result<SshKey, String> {
+    let mut remaining = blob;
+    if read_string(&mut remaining)? != key_type.as_bytes() {
+        return Err("The key type does not match the key data".to_string());
+    }
+
+    let key = match key_type {
+        KEY_TYPE_ED25519 => {
+            let point = read_string(&mut remaining)?;
+            if point.len() != 32 {
+                return Err("An ed25519 key must be 32 bytes long".to_string());
+            }
+            SshKey::Ed25519(Ed25519::deserialize_point(&hex::encode(point))?)
+        }
+        _ => {
+            if read_string(&mut remaining)? != b"nistp256" {
+                return Err("Invalid ecdsa curve identifier".to_string());
+            }
+            let public_key = p256::PublicKey::from_sec1_bytes(read_string(&mut remaining)?)
+                .map_err(|_| "Invalid P-256 point".to_string())?;
+            SshKey::EcdsaP256(*public_key.as_affine())
+        }
+    };
+
+    if !remaining.is_empty() {
+        return Err("Unexpected trailing bytes in key data".to_string());
+    }
+    Ok(key)
+}
+
+/// Reads a SSH string: a big endian u32 length and the bytes
+fn read_string<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    if bytes.len() < 4 {
+        return Err("Unexpected end of key data".to_string());
+    }
+    let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let string = bytes
+        .get(4..4 + length)
+        .ok_or_else(|| "Unexpected end of key data".to_string())?;
+
+    *bytes = &bytes[4 + length..];
+    Ok(string)
+}
+
+fn encode_string(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = (bytes.len() as u32).to_be_bytes().to_vec();
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ed25519_key(private_key: u64, comment: &str) -> SshPublicKey {
+        SshPublicKey {
+            key: SshKey::Ed25519(Ed25519::mul_generator(&curve25519_dalek::Scalar::from(
+                private_key,
+            ))),
+            comment: Some(comment.to_string()),
+        }
+    }
+
+    fn p256_key(private_key: u64) -> SshPublicKey {
+        SshPublicKey {
+            key: SshKey::EcdsaP256(
+                (p256::AffinePoint::GENERATOR * p256::Scalar::from(private_key)).to_affine(),
+            ),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_authorized_keys() {
+        let alice = ed25519_key(2, "alice@laptop");
+        let bob = p256_key(3);
+        let carol = ed25519_key(4, "carol");
+
+        let content = format!(
+            "# team keys\n{}\n\nssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ dave\n{}\nno-pty,command=\"/bin/true\" {}\n",
+            alice.to_openssh(),
+            bob.to_openssh(),
+            carol.to_openssh()
+        );
+        let keys = parse_authorized_keys(&content).expect("failed to parse the keys");
+
+        assert_eq!(keys.ed25519, vec![alice, carol]);
+        assert_eq!(keys.p256, vec![bob]);
+        assert_eq!(keys.ed25519_points().len(), 2);
+        assert_eq!(keys.p256_points().len(), 1);
+        assert_eq!(keys.p256_ring().unwrap().points(), keys.p256_points());
+        assert_eq!(keys.ed25519_ring().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let alice = ed25519_key(2, "alice");
+        let line = format!(
+            "command=\"echo \\\"hi\\\" ssh-ed25519 x\",no-pty {}",
+            alice.to_openssh()
+        );
+        assert_eq!(SshPublicKey::parse(&line), Ok(alice.clone()));
+
+        // unsupported key types are skipped, even when a supported key type appears later
+        let content = format!(
+            "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ ssh-ed25519 key\n\
+             command=\"/bin/check ssh-ed25519 AAAA\" ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ\n\
+             from=\"10.0.0.1\" sk-ssh-ed25519@openssh.com AAAAGnNrLXNzaC1lZDI1NTE5QG9wZW5zc2guY29t\n{}",
+            alice.to_openssh()
+        );
+        let keys = parse_authorized_keys(&content).expect("failed to parse the keys");
+        assert_eq!(keys.ed25519, vec![alice]);
+        assert!(keys.p256.is_empty());
+
+        assert_eq!(
+            SshPublicKey::parse("ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ ssh-ed25519"),
+            Err("Unsupported SSH key type: ssh-rsa".to_string())
+        );
+        assert!(SshPublicKey::parse("command=\"unterminated ssh-ed25519 AAAA").is_err());
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let alice = ed25519_key(2, "alice").to_openssh();
+
+        // truncated key data
+        let truncated = alice.replace("AAAAC3NzaC1lZDI1NTE5AAAAI", "AAAAC3NzaC1lZDI1NTE5AAAAE");
+        assert!(parse_authorized_keys(&truncated).is_err());
+        // mismatching key type
+        let mismatching = alice.replace(KEY_TYPE_ED25519, KEY_TYPE_ECDSA_P256);
+        assert!(SshPublicKey::parse(&mismatching).is_err());
+        assert!(SshPublicKey::parse("ssh-ed25519").is_err());
+    }
+
+    #[test]
+    fn test_armor() {
+        let signature: Vec<u8> = (0..=255).collect();
+        let armored = armor(&signature);
+
+        assert!(armored.starts_with(ARMOR_BEGIN));
+        assert!(armored
+            .lines()
+            .all(|line| line.len() <= ARMOR_LINE_LENGTH || line.starts_with("-----")));
+        assert_eq!(dearmor(&armored), Ok(signature));
+        assert!(dearmor("not armored").is_err());
+    }
+
+    #[test]
+    fn test_sign_armored() {
+        let content = [2, 3, 4]
+            .map(|k| ed25519_key(k, "dev").to_openssh())
+            .join("\n");
+        let ring = parse_authorized_keys(&content)
+            .unwrap()
+            .ed25519_ring()
+            .unwrap();
+        let message = "release v1.2.3 is approved";
+
+        let armored = sign_armored(
+            &ring,
+            curve25519_dalek::Scalar::from(3u64),
+            message.to_string(),
+            Some("releases".to_string()),
+        )
+        .expect("failed to sign");
+        let signature = verify_armored(&armored, &ring, message).expect("failed to verify");
+        assert_eq!(signature.linkability_flag.as_deref(), Some("releases"));
+
+        assert!(verify_armored(&armored, &ring, "another message").is_err());
+        let other_ring = GenericRing::<Ed25519>::new(vec![ring[0], ring[1]]).unwrap();
+        assert!(verify_armored(&armored, &other_ring, message).is_err());
+        // the signer must be a ring member
+        assert!(sign_armored(
+            &ring,
+            curve25519_dalek::Scalar::from(5u64),
+            message.to_string(),
+            None
+        )
+        .is_err());
+
+        // P-256 keys sign the same way
+        let p256_ring = GenericRing::<NistP256>::new(vec![p256_points(2), p256_points(3)]).unwrap();
+        let armored = sign_armored(
+            &p256_ring,
+            p256::Scalar::from(2u64),
+            message.to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(verify_armored(&armored, &p256_ring, message).is_ok());
+    }
+
+    fn p256_points(private_key: u64) -> p256::AffinePoint {
+        match p256_key(private_key).key {
+            SshKey::EcdsaP256(point) => point,
+            _ => unreachable!(),
+        }
+    }
+}
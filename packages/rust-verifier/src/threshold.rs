@@ -0,0 +1,208 @@
+//! Threshold ring signatures: at least t members of a ring signed a message, without revealing
+//! which ones.
+//!
+//! A threshold signature is a set of LSAG signatures of the same message, ring and linkability
+//! flag. Their key images must all be different, so each one comes from a different member:
+//! a member signing twice produces the same key image and is counted once.
+//!
+//! For committee approvals, each member signs the proposal with `sign_lsag` on the agreed ring,
+//! with the proposal id as linkability flag, and the signatures are collected with
+//! `ThresholdSignature::from_signatures`.
+use crate::lsag_signer::sign_lsag;
+use crate::lsag_verifier::{verify_lsag, Lsag, StringifiedLsag};
+use crate::ring::Ring;
+use crate::utils::curve::RingCurve;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+
+/// The JSON representation of a threshold signature
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StringifiedThresholdSignature {
+    pub signatures: Vec<StringifiedLsag>,
+}
+
+/// A threshold ring signature: one LSAG signature per signer
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdSignature {
+    pub signatures: Vec<Lsag>,
+}
+
+impl ThresholdSignature {
+    /// Collects LSAG signatures of the same message, ring and linkability flag
+    pub fn from_signatures(signatures: Vec<Lsag>) -> Result<ThresholdSignature, String> {
+        let signature = ThresholdSignature { signatures };
+        signature.check_consistency()?;
+
+        Ok(signature)
+    }
+
+    /// Parses a threshold signature from its JSON string
+    pub fn from_json(json: &str) -> Result<ThresholdSignature, String> {
+        let stringified: StringifiedThresholdSignature =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        ThresholdSignature::from_signatures(
+            stringified
+                .signatures
+                .into_iter()
+                .map(Lsag::try_from)
+                .collect::<Result<Vec<Lsag>, String>>()?,
+        )
+    }
+
+    /// Converts the signature to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&StringifiedThresholdSignature {
+            signatures: self.signatures.iter().map(StringifiedLsag::from).collect(),
+        })
+        .unwrap()
+    }
+
+    /// Returns the key images of the signers
+    pub fn key_images(&self) -> Vec<AffinePoint> {
+        self.signatures
+            .iter()
+            .map(|signature| signature.key_image)
+            .collect()
+    }
+
+    /// Checks that the signatures share the message, ring and linkability flag,
+    /// and that they come from different signers
+    fn check_consistency(&self) -> Result<(), String> {
+        let first = self
+            .signatures
+            .first()
+            .ok_or_else(|| "A threshold signature needs at least one signature".to_string())?;
+
+        for (i, signature) in self.signatures.iter().enumerate() {
+            if signature.message != first.message {
+                return Err("The signatures must have the same message".to_string());
+            }
+            if signature.ring != first.ring {
+                return Err("The signatures must have the same ring".to_string());
+            }
+            if signature.linkability_flag.as_deref().unwrap_or("")
+                != first.linkability_flag.as_deref().unwrap_or("")
+            {
+                return Err("The signatures must have the same linkability flag".to_string());
+            }
+            if self.signatures[..i]
+                .iter()
+                .any(|other| other.key_image == signature.key_image)
+            {
+                return Err(format!(
+                    "Duplicated signer with key image {}",
+                    Secp256k1::serialize_point(&signature.key_image)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Signs a message with several members of a ring. The signer public keys are inserted in the
+/// ring if needed, at their sorted position.
+pub fn sign_threshold(
+    ring: &[AffinePoint],
+    signer_private_keys: &[Scalar],
+    message: String,
+    linkability_flag: Option<String>,
+) -> Result<ThresholdSignature, String> {
+    let mut full_ring = Ring::from_sorted(ring.to_vec())?;
+    for private_key in signer_private_keys {
+        full_ring = full_ring
+            .with_member(Secp256k1::mul_generator(private_key))?
+            .0;
+    }
+
+    let signatures = signer_private_keys
+        .iter()
+        .map(|private_key| {
+            sign_lsag(
+                &full_ring,
+                *private_key,
+                message.clone(),
+                linkability_flag.clone(),
+            )
+        })
+        .collect::<Result<Vec<Lsag>, String>>()?;
+
+    ThresholdSignature::from_signatures(signatures)
+}
+
+/// Verifies a threshold signature.
+/// Returns the number of distinct signers, or an error if a signature is invalid, if the
+/// signatures do not share the message, ring and linkability flag, or if a signer is duplicated.
+pub fn verify_threshold(signature: &ThresholdSignature) -> Result<usize, String> {
+    signature.check_consistency()?;
+
+    for (i, lsag) in signature.signatures.iter().enumerate() {
+        if !verify_lsag(lsag.clone()) {
+            return Err(format!("Invalid signature at index {}", i));
+        }
+    }
+
+    Ok(signature.signatures.len())
+}
+
+/// Verifies that at least `threshold` distinct members of the ring signed.
+/// Returns `true` if the signature is valid, `false` otherwise.
+pub fn verify_threshold_at_least(signature: &ThresholdSignature, threshold: usize) -> bool {
+    verify_threshold(signature).is_ok_and(|signers| signers >= threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::get_sorted_ring;
+
+    #[test]
+    fn test_sign_threshold() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5, 6]);
+        let signers = [Scalar::from(3u64), Scalar::from(5u64), Scalar::from(42u64)];
+        let flag = Some("proposal 1".to_string());
+
+        let signature = sign_threshold(&ring, &signers, "approve".to_string(), flag.clone())
+            .expect("failed to sign");
+        assert_eq!(signature.signatures[0].ring.len(), 6);
+        assert_eq!(verify_threshold(&signature), Ok(3));
+        assert!(verify_threshold_at_least(&signature, 3));
+        assert!(!verify_threshold_at_least(&signature, 4));
+
+        let parsed = ThresholdSignature::from_json(&signature.to_json()).unwrap();
+        assert_eq!(verify_threshold(&parsed), Ok(3));
+
+        // a member signing twice has the same key image
+        let full_ring = &signature.signatures[0].ring;
+        let again = sign_lsag(full_ring, signers[0], "approve".to_string(), flag).unwrap();
+        let mut signatures = signature.signatures.clone();
+        signatures.push(again);
+        assert!(ThresholdSignature::from_signatures(signatures.clone()).is_err());
+        assert!(verify_threshold(&ThresholdSignature { signatures }).is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_signatures() {
+        let ring = get_sorted_ring(&[2, 3, 4, 5]);
+        let first = sign_lsag(&ring, Scalar::from(2u64), "approve".to_string(), None).unwrap();
+
+        let other_message = sign_lsag(&ring, Scalar::from(3u64), "reject".to_string(), None);
+        assert!(
+            ThresholdSignature::from_signatures(vec![first.clone(), other_message.unwrap()])
+                .is_err()
+        );
+
+        let other_ring = sign_lsag(&ring[1..], Scalar::from(3u64), "approve".to_string(), None);
+        assert!(
+            ThresholdSignature::from_signatures(vec![first.clone(), other_ring.unwrap()]).is_err()
+        );
+
+        let mut tampered =
+            sign_lsag(&ring, Scalar::from(3u64), "approve".to_string(), None).unwrap();
+        tampered.c0 += Scalar::ONE;
+        let signature = ThresholdSignature::from_signatures(vec![first, tampered]).unwrap();
+        assert!(verify_threshold(&signature).is_err());
+        assert!(ThresholdSignature::from_signatures(vec![]).is_err());
+    }
+}
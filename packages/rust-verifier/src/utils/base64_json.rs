@@ -0,0 +1,29 @@
+use base64::engine::general_purpose;
+use base64::Engine;
+
+/// Decodes the base64 encoding of a JSON string, as written by the `toBase64()` functions
+/// of the TS libraries
+pub fn decode_base64_json(b64: &str) -> Result<String, String> {
+    let decoded_bytes = general_purpose::STANDARD
+        .decode(b64.as_bytes())
+        .map_err(|e| format!("Invalid base64 string: {}", e))?;
+
+    String::from_utf8(decoded_bytes)
+        .map_err(|_| "Failed to convert decoded bytes to UTF-8 string".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64_json() {
+        let b64 = general_purpose::STANDARD.encode(r#"{"message":"hello"}"#);
+        assert_eq!(
+            decode_base64_json(&b64),
+            Ok(r#"{"message":"hello"}"#.to_string())
+        );
+        assert!(decode_base64_json("not base64!").is_err());
+        assert!(decode_base64_json(&general_purpose::STANDARD.encode([0xff, 0xfe])).is_err());
+    }
+}
@@ -0,0 +1,609 @@
+use super::eth_address::generic_eth_address_bytes;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::clamp_integer;
+use elliptic_curve::group::{cofactor::CofactorGroup, Curve as _, Group};
+use elliptic_curve::hash2curve::{ExpandMsg, ExpandMsgXmd, Expander, GroupDigest};
+use elliptic_curve::ops::Reduce;
+use elliptic_curve::sec1::{EncodedPoint, FromEncodedPoint, ModulusSize, ToEncodedPoint};
+use elliptic_curve::{
+    AffinePoint, CurveArithmetic, Field, FieldBytes, PrimeField, ProjectivePoint, Scalar,
+};
+use k256::Secp256k1;
+use p256::NistP256;
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+/// The elliptic curves a ring signature can be computed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Curve {
+    #[serde(rename = "SECP256K1")]
+    Secp256k1,
+    #[serde(rename = "P256")]
+    P256,
+    #[serde(rename = "RISTRETTO255")]
+    Ristretto255,
+    #[serde(rename = "ED25519")]
+    Ed25519,
+}
+
+/// The JSON representation of a curve, as produced by `Curve.toString()` in the TS libraries
+#[derive(Serialize, Deserialize)]
+struct StringifiedCurve {
+    curve: Curve,
+}
+
+impl Curve {
+    /// Returns the curve name as used by the TS libraries
+    pub fn name(&self) -> &'static str {
+        match self {
+            Curve::Secp256k1 => "SECP256K1",
+            Curve::P256 => "P256",
+            Curve::Ristretto255 => "RISTRETTO255",
+            Curve::Ed25519 => "ED25519",
+        }
+    }
+
+    /// Parses a curve from its name (e.g. `SECP256K1`)
+    pub fn from_name(name: &str) -> Result<Curve, String> {
+        match name {
+            "SECP256K1" => Ok(Curve::Secp256k1),
+            "P256" => Ok(Curve::P256),
+            "RISTRETTO255" => Ok(Curve::Ristretto255),
+            "ED25519" => Ok(Curve::Ed25519),
+            _ => Err(format!("Unsupported curve: {}", name)),
+        }
+    }
+
+    /// Converts the curve to its JSON string representation (e.g. `{"curve":"SECP256K1"}`)
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(&StringifiedCurve { curve: *self }).unwrap()
+    }
+
+    /// Parses a curve from its JSON string representation
+    pub fn from_json_string(curve: &str) -> Result<Curve, String> {
+        let parsed: StringifiedCurve =
+            serde_json::from_str(curve).map_err(|e| format!("Unsupported curve: {}", e))?;
+
+        Ok(parsed.curve)
+    }
+}
+
+/// A group the ring signatures can be computed on.
+///
+/// Points are serialized as hex strings and scalars as big endian hex strings without leading
+/// zeros, as the TS libraries do for secp256k1. The signature algorithms only use the
+/// operations below, so a curve that does not implement the RustCrypto traits (e.g.
+/// `Ristretto255`) can still be used.
+pub trait RingCurve: Clone + Debug + Eq {
+    /// The curve, as written in the signatures
+    const CURVE: Curve;
+
+    /// A point of the curve. The default point is the identity.
+    type Point: Copy + Debug + Default + Eq;
+
+    /// A scalar modulo the order of the curve
+    type Scalar: PrimeField;
+
+    /// Returns the generator `G` of the curve
+    fn generator() -> Self::Point;
+
+    /// Computes `scalar * G`
+    fn mul_generator(scalar: &Self::Scalar) -> Self::Point;
+
+    /// Computes `scalar * point`
+    fn mul(point: &Self::Point, scalar: &Self::Scalar) -> Self::Point;
+
+    /// Computes `a * p + b * q`
+    fn lincomb(p: &Self::Point, a: &Self::Scalar, q: &Self::Point, b: &Self::Scalar)
+        -> Self::Point;
+
+    /// Hashes a message to a point of the curve, to compute the key images
+    fn hash_to_curve(message: String) -> Self::Point;
+
+    /// Serializes a point to a compressed hexadecimal string
+    fn serialize_point(point: &Self::Point) -> String;
+
+    /// Deserializes a compressed hexadecimal string to a point
+    fn deserialize_point(hex_str: &str) -> Result<Self::Point, String>;
+
+    /// Compares two points, to sort the rings
+    fn compare_points(a: &Self::Point, b: &Self::Point) -> Ordering;
+
+    /// Parses a scalar from a hex string, which does not have to be zero padded
+    fn scalar_from_hex(hex_string: &str) -> Result<Self::Scalar, String>;
+
+    /// Converts a scalar to a hex string without leading zeros,
+    /// as `bigint.toString(16)` does in the TS libraries
+    fn scalar_to_hex(scalar: &Self::Scalar) -> String;
+
+    /// Reduces a 32-byte hash to a scalar
+    fn scalar_from_hash(hash: &[u8]) -> Self::Scalar;
+
+    /// Returns the ethereum address of a point, if the curve has one
+    fn eth_address(_point: &Self::Point) -> Option<[u8; 20]> {
+        None
+    }
+
+    /// Generates a random non-zero scalar
+    fn random_scalar() -> Self::Scalar {
+        loop {
+            let scalar = Self::Scalar::random(&mut OsRng);
+            if !bool::from(scalar.is_zero()) {
+                return scalar;
+            }
+        }
+    }
+}
+
+/// A short Weierstrass curve of the RustCrypto crates, such as secp256k1 or P-256.
+///
+/// Points are serialized as compressed SEC1 points and key images use the `hash_to_curve` of
+/// RFC 9380 (expand_message_xmd with SHA-256), so supporting a new curve only takes its `CURVE`
+/// and `DST`.
+pub trait Sec1Curve:
+    CurveArithmetic<
+        AffinePoint: FromEncodedPoint<Self> + ToEncodedPoint<Self>,
+        ProjectivePoint: CofactorGroup,
+        FieldBytesSize: ModulusSize,
+    > + GroupDigest
+{
+    /// The curve, as written in the signatures
+    const CURVE: Curve;
+
+    /// The domain separation tag of the hash to curve function
+    const DST: &'static [u8];
+}
+
+impl<C: Sec1Curve> RingCurve for C {
+    const CURVE: Curve = <C as Sec1Curve>::CURVE;
+
+    type Point = AffinePoint<C>;
+    type Scalar = Scalar<C>;
+
+    fn generator() -> AffinePoint<C> {
+        ProjectivePoint::<C>::generator().to_affine()
+    }
+
+    fn mul_generator(scalar: &Scalar<C>) -> AffinePoint<C> {
+        (ProjectivePoint::<C>::generator() * scalar).to_affine()
+    }
+
+    fn mul(point: &AffinePoint<C>, scalar: &Scalar<C>) -> AffinePoint<C> {
+        (ProjectivePoint::<C>::from(*point) * scalar).to_affine()
+    }
+
+    fn lincomb(
+        p: &AffinePoint<C>,
+        a: &Scalar<C>,
+        q: &AffinePoint<C>,
+        b: &Scalar<C>,
+    ) -> AffinePoint<C> {
+        (ProjectivePoint::<C>::from(*p) * a + ProjectivePoint::<C>::from(*q) * b).to_affine()
+    }
+
+    fn hash_to_curve(message: String) -> AffinePoint<C> {
+        C::hash_from_bytes::<ExpandMsgXmd<Sha256>>(&[message.as_bytes()], &[C::DST])
+            .unwrap()
+            .to_affine()
+    }
+
+    fn serialize_point(point: &AffinePoint<C>) -> String {
+        hex::encode(point.to_encoded_point(true).as_bytes())
+    }
+
+    fn deserialize_point(hex_str: &str) -> Result<AffinePoint<C>, String> {
+        let bytes = hex::decode(hex_str).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+        if bytes.len() != FieldBytes::<C>::default().len() + 1 {
+            return Err("Invalid length for a compressed point".to_string());
+        }
+
+        let encoded_point = EncodedPoint::<C>::from_bytes(&bytes)
+            .map_err(|_| "Invalid compressed point encoding".to_string())?;
+
+        Option::from(AffinePoint::<C>::from_encoded_point(&encoded_point))
+            .ok_or_else(|| "Point is not on the curve".to_string())
+    }
+
+    /// Compares two points by x coordinate, then by y coordinate if the x's are equal
+    fn compare_points(a: &AffinePoint<C>, b: &AffinePoint<C>) -> Ordering {
+        // big endian coordinates compare the same way as the integers they encode
+        let a = a.to_encoded_point(false);
+        let b = b.to_encoded_point(false);
+
+        a.x().cmp(&b.x()).then_with(|| a.y().cmp(&b.y()))
+    }
+
+    fn scalar_from_hex(hex_string: &str) -> Result<Scalar<C>, String> {
+        let mut scalar_bytes = FieldBytes::<C>::default();
+        decode_padded_hex(hex_string, &mut scalar_bytes)?;
+
+        Option::from(Scalar::<C>::from_repr(scalar_bytes))
+            .ok_or_else(|| "Scalar must be lower than the curve order".to_string())
+    }
+
+    fn scalar_to_hex(scalar: &Scalar<C>) -> String {
+        trimmed_hex(&scalar.to_repr())
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> Scalar<C> {
+        let mut bytes = FieldBytes::<C>::default();
+        bytes.copy_from_slice(hash);
+
+        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&bytes)
+    }
+
+    fn eth_address(point: &AffinePoint<C>) -> Option<[u8; 20]> {
+        Some(generic_eth_address_bytes::<C>(point))
+    }
+}
+
+impl Sec1Curve for Secp256k1 {
+    const CURVE: Curve = Curve::Secp256k1;
+    const DST: &'static [u8] = b"secp256k1_XMD:SHA-256_SSWU_RO_";
+}
+
+impl Sec1Curve for NistP256 {
+    const CURVE: Curve = Curve::P256;
+    const DST: &'static [u8] = b"P256_XMD:SHA-256_SSWU_RO_";
+}
+
+/// The ristretto255 group of RFC 9496, built on curve25519.
+///
+/// Points are serialized as their 32-byte encoding and sorted by it. Scalars are written in big
+/// endian, like on the other curves. Key images use the `hash_to_ristretto255` of RFC 9380
+/// (expand_message_xmd with SHA-512).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ristretto255;
+
+impl Ristretto255 {
+    /// The domain separation tag of the hash to group function
+    pub const DST: &'static [u8] = b"ristretto255_XMD:SHA-512_R255MAP_RO_";
+}
+
+impl RingCurve for Ristretto255 {
+    const CURVE: Curve = Curve::Ristretto255;
+
+    type Point = RistrettoPoint;
+    type Scalar = curve25519_dalek::Scalar;
+
+    fn generator() -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn mul_generator(scalar: &Self::Scalar) -> RistrettoPoint {
+        RistrettoPoint::mul_base(scalar)
+    }
+
+    fn mul(point: &RistrettoPoint, scalar: &Self::Scalar) -> RistrettoPoint {
+        point * scalar
+    }
+
+    fn lincomb(
+        p: &RistrettoPoint,
+        a: &Self::Scalar,
+        q: &RistrettoPoint,
+        b: &Self::Scalar,
+    ) -> RistrettoPoint {
+        p * a + q * b
+    }
+
+    fn hash_to_curve(message: String) -> RistrettoPoint {
+        let mut uniform_bytes = [0u8; 64];
+        ExpandMsgXmd::<Sha512>::expand_message(&[message.as_bytes()], &[Self::DST], 64)
+            .unwrap()
+            .fill_bytes(&mut uniform_bytes);
+
+        RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+    }
+
+    fn serialize_point(point: &RistrettoPoint) -> String {
+        hex::encode(point.compress().as_bytes())
+    }
+
+    fn deserialize_point(hex_str: &str) -> Result<RistrettoPoint, String> {
+        let bytes = hex::decode(hex_str).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+        let compressed = CompressedRistretto::from_slice(&bytes)
+            .map_err(|_| "Invalid length for a compressed point".to_string())?;
+
+        compressed
+            .decompress()
+            .ok_or_else(|| "Invalid ristretto255 point encoding".to_string())
+    }
+
+    /// Compares two points by their encoding
+    fn compare_points(a: &RistrettoPoint, b: &RistrettoPoint) -> Ordering {
+        a.compress().as_bytes().cmp(b.compress().as_bytes())
+    }
+
+    fn scalar_from_hex(hex_string: &str) -> Result<Self::Scalar, String> {
+        dalek_scalar_from_hex(hex_string)
+    }
+
+    fn scalar_to_hex(scalar: &Self::Scalar) -> String {
+        dalek_scalar_to_hex(scalar)
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> Self::Scalar {
+        dalek_scalar_from_hash(hash)
+    }
+}
+
+/// The edwards25519 curve of ed25519 keys (e.g. `ssh-ed25519` keys).
+///
+/// Points are serialized as their 32-byte ed25519 encoding and sorted by it, scalars are
+/// big endian like on the other curves. The curve has a cofactor of 8: points that are not
+/// in the prime order subgroup are rejected when deserialized, and key images use a
+/// try-and-increment hash (expand_message_xmd with SHA-512) whose output is multiplied by the
+/// cofactor, so a signer has a single key image per flag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ed25519;
+
+impl Ed25519 {
+    /// The domain separation tag of the hash to curve function
+    pub const DST: &'static [u8] = b"edwards25519_XMD:SHA-512_TAI_";
+
+    /// Returns the private scalar of an ed25519 key from its 32-byte seed, as in RFC 8032:
+    /// the first half of the SHA-512 hash of the seed, clamped
+    pub fn scalar_from_seed(seed: &[u8; 32]) -> curve25519_dalek::Scalar {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&Sha512::digest(seed)[..32]);
+
+        curve25519_dalek::Scalar::from_bytes_mod_order(clamp_integer(bytes))
+    }
+}
+
+impl RingCurve for Ed25519 {
+    const CURVE: Curve = Curve::Ed25519;
+
+    type Point = EdwardsPoint;
+    type Scalar = curve25519_dalek::Scalar;
+
+    fn generator() -> EdwardsPoint {
+        ED25519_BASEPOINT_POINT
+    }
+
+    fn mul_generator(scalar: &Self::Scalar) -> EdwardsPoint {
+        EdwardsPoint::mul_base(scalar)
+    }
+
+    fn mul(point: &EdwardsPoint, scalar: &Self::Scalar) -> EdwardsPoint {
+        point * scalar
+    }
+
+    fn lincomb(
+        p: &EdwardsPoint,
+        a: &Self::Scalar,
+        q: &EdwardsPoint,
+        b: &Self::Scalar,
+    ) -> EdwardsPoint {
+        p * a + q * b
+    }
+
+    fn hash_to_curve(message: String) -> EdwardsPoint {
+        for counter in 0..=u8::MAX {
+            let mut candidate = [0u8; 32];
+            ExpandMsgXmd::<Sha512>::expand_message(
+                &[message.as_bytes(), &[counter]],
+                &[Self::DST],
+                32,
+            )
+            .unwrap()
+            .fill_bytes(&mut candidate);
+
+            // clear the cofactor to land in the prime order subgroup
+            if let Some(point) = CompressedEdwardsY(candidate).decompress() {
+                let point = point.mul_by_cofactor();
+                if !point.is_small_order() {
+                    return point;
+                }
+            }
+        }
+
+        // each candidate is a valid point with probability 1/2
+        unreachable!("No ed25519 point found after 256 attempts")
+    }
+
+    fn serialize_point(point: &EdwardsPoint) -> String {
+        hex::encode(point.compress().as_bytes())
+    }
+
+    fn deserialize_point(hex_str: &str) -> Result<EdwardsPoint, String> {
+        let bytes = hex::decode(hex_str).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+        let compressed = CompressedEdwardsY::from_slice(&bytes)
+            .map_err(|_| "Invalid length for a compressed point".to_string())?;
+        let point = compressed
+            .decompress()
+            .filter(|point| point.compress() == compressed)
+            .ok_or_else(|| "Invalid ed25519 point encoding".to_string())?;
+
+        if point.is_small_order() || !point.is_torsion_free() {
+            return Err("The ed25519 point is not in the prime order subgroup".to_string());
+        }
+        Ok(point)
+    }
+
+    /// Compares two points by their encoding
+    fn compare_points(a: &EdwardsPoint, b: &EdwardsPoint) -> Ordering {
+        a.compress().as_bytes().cmp(b.compress().as_bytes())
+    }
+
+    fn scalar_from_hex(hex_string: &str) -> Result<Self::Scalar, String> {
+        dalek_scalar_from_hex(hex_string)
+    }
+
+    fn scalar_to_hex(scalar: &Self::Scalar) -> String {
+        dalek_scalar_to_hex(scalar)
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> Self::Scalar {
+        dalek_scalar_from_hash(hash)
+    }
+}
+
+/// Parses a big endian hex string to a curve25519-dalek scalar, which is little endian
+fn dalek_scalar_from_hex(hex_string: &str) -> Result<curve25519_dalek::Scalar, String> {
+    let mut scalar_bytes = [0u8; 32];
+    decode_padded_hex(hex_string, &mut scalar_bytes)?;
+    scalar_bytes.reverse();
+
+    Option::from(curve25519_dalek::Scalar::from_canonical_bytes(scalar_bytes))
+        .ok_or_else(|| "Scalar must be lower than the curve order".to_string())
+}
+
+/// Converts a curve25519-dalek scalar to a big endian hex string without leading zeros
+fn dalek_scalar_to_hex(scalar: &curve25519_dalek::Scalar) -> String {
+    let mut bytes = scalar.to_bytes();
+    bytes.reverse();
+
+    trimmed_hex(&bytes)
+}
+
+/// Reduces a 32-byte hash, read as a big endian integer, to a curve25519-dalek scalar
+fn dalek_scalar_from_hash(hash: &[u8]) -> curve25519_dalek::Scalar {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(hash);
+    bytes.reverse();
+
+    curve25519_dalek::Scalar::from_bytes_mod_order(bytes)
+}
+
+/// Decodes a hex string that is not zero padded into `bytes`, as a big endian integer
+fn decode_padded_hex(hex_string: &str, bytes: &mut [u8]) -> Result<(), String> {
+    let hex_string = hex_string.trim_start_matches("0x");
+    let hex_string = if hex_string.len() % 2 == 1 {
+        format!("0{}", hex_string)
+    } else {
+        hex_string.to_string()
+    };
+    let hex_bytes =
+        hex::decode(hex_string).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+    let size = bytes.len();
+    if hex_bytes.len() > size {
+        return Err(format!("Scalar must be at most {} bytes long", size));
+    }
+    bytes[size - hex_bytes.len()..].copy_from_slice(&hex_bytes);
+
+    Ok(())
+}
+
+/// Encodes big endian bytes to hex without leading zeros
+fn trimmed_hex(bytes: &[u8]) -> String {
+    let padded = hex::encode(bytes);
+    let trimmed = padded.trim_start_matches('0');
+
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_serialization<C: RingCurve>(point_length: usize) {
+        let point = C::mul_generator(&C::Scalar::from(42u64));
+        let serialized = C::serialize_point(&point);
+
+        assert_eq!(serialized.len(), point_length);
+        assert_eq!(C::deserialize_point(&serialized), Ok(point));
+        assert!(C::deserialize_point(&serialized[2..]).is_err());
+
+        let scalar = C::Scalar::from(0xabcu64);
+        assert_eq!(C::scalar_to_hex(&scalar), "abc");
+        assert_eq!(C::scalar_from_hex("abc"), Ok(scalar));
+        assert_eq!(C::scalar_to_hex(&C::Scalar::ZERO), "0");
+        assert_eq!(
+            C::lincomb(&C::generator(), &scalar, &point, &C::Scalar::ONE),
+            C::mul_generator(&(scalar + C::Scalar::from(42u64)))
+        );
+
+        // the hash to curve depends on the curve DST
+        assert_ne!(
+            C::hash_to_curve("a".to_string()),
+            C::hash_to_curve("b".to_string())
+        );
+        assert_eq!(
+            Curve::from_json_string(&C::CURVE.to_json_string()),
+            Ok(C::CURVE)
+        );
+    }
+
+    #[test]
+    fn test_ring_curves() {
+        check_serialization::<Secp256k1>(66);
+        check_serialization::<NistP256>(66);
+        check_serialization::<Ristretto255>(64);
+        check_serialization::<Ed25519>(64);
+        assert_eq!(Curve::P256.to_json_string(), r#"{"curve":"P256"}"#);
+        assert_eq!(
+            Curve::Ristretto255.to_json_string(),
+            r#"{"curve":"RISTRETTO255"}"#
+        );
+    }
+
+    #[test]
+    fn test_ristretto255_encoding() {
+        // the generator encoding of RFC 9496
+        assert_eq!(
+            Ristretto255::serialize_point(&Ristretto255::generator()),
+            "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76"
+        );
+        // scalars are big endian, as on the other curves
+        assert_eq!(
+            Ristretto255::scalar_from_hex("0100"),
+            Ok(curve25519_dalek::Scalar::from(256u64))
+        );
+        // the curve order is not a valid scalar
+        assert!(Ristretto255::scalar_from_hex(
+            "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ed25519_cofactor() {
+        // the generator encoding of RFC 8032
+        assert_eq!(
+            Ed25519::serialize_point(&Ed25519::generator()),
+            "5866666666666666666666666666666666666666666666666666666666666666"
+        );
+        // the identity and the other small order points are rejected
+        let identity = format!("01{}", "00".repeat(31));
+        assert!(Ed25519::deserialize_point(&identity).is_err());
+        // a point with a torsion component is rejected
+        let torsion = Ed25519::generator()
+            + CompressedEdwardsY::from_slice(
+                &hex::decode("26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05")
+                    .unwrap(),
+            )
+            .unwrap()
+            .decompress()
+            .unwrap();
+        assert!(Ed25519::deserialize_point(&Ed25519::serialize_point(&torsion)).is_err());
+
+        // key images are in the prime order subgroup
+        assert!(Ed25519::hash_to_curve("a".to_string()).is_torsion_free());
+
+        // the public key of the RFC 8032 test vector 1
+        let seed: [u8; 32] =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            Ed25519::serialize_point(&Ed25519::mul_generator(&Ed25519::scalar_from_seed(&seed))),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+    }
+}
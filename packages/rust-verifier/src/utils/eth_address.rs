@@ -0,0 +1,99 @@
+use super::curve::Sec1Curve;
+use elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, Secp256k1};
+use sha3::{Digest, Keccak256};
+
+/// Computes the ethereum address of a point, as `Point.toEthAddress()` does in the TS libraries:
+/// the last 20 bytes of the keccak256 hash of the uncompressed point (without its `04` prefix)
+pub fn eth_address_bytes(point: &AffinePoint) -> [u8; 20] {
+    generic_eth_address_bytes::<Secp256k1>(point)
+}
+
+/// Computes the ethereum address of a point on any supported curve (see `eth_address_bytes`)
+pub fn generic_eth_address_bytes<C: Sec1Curve>(point: &elliptic_curve::AffinePoint<C>) -> [u8; 20] {
+    let encoded = point.to_encoded_point(false);
+    let hash = Keccak256::digest(&encoded.as_bytes()[1..]);
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Computes the EIP-55 checksummed ethereum address of a point (e.g. `0x9d8A62f6...`)
+pub fn to_eth_address(point: &AffinePoint) -> String {
+    to_checksum_address(&eth_address_bytes(point))
+}
+
+/// Formats an address with the EIP-55 mixed-case checksum
+pub fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lowercase = hex::encode(address);
+    let hash = Keccak256::digest(lowercase.as_bytes());
+
+    let checksummed: String = lowercase
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            // a letter is uppercased if the matching nibble of the hash is >= 8
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    format!("0x{}", checksummed)
+}
+
+/// Parses a 0x-prefixed hex address.
+/// Mixed-case addresses must have a valid EIP-55 checksum.
+pub fn parse_eth_address(address: &str) -> Result<[u8; 20], String> {
+    let hex_address = address.strip_prefix("0x").unwrap_or(address);
+    let bytes: [u8; 20] = hex::decode(hex_address)
+        .map_err(|_| format!("Invalid address: {}", address))?
+        .try_into()
+        .map_err(|_| format!("Invalid address: {}", address))?;
+
+    let is_mixed_case = hex_address.chars().any(|c| c.is_ascii_lowercase())
+        && hex_address.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case && to_checksum_address(&bytes)[2..] != *hex_address {
+        return Err(format!("Invalid address checksum: {}", address));
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::Scalar;
+
+    #[test]
+    fn test_checksum_address() {
+        // test vectors from EIP-55
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let bytes = parse_eth_address(address).unwrap();
+            assert_eq!(to_checksum_address(&bytes), address);
+            assert_eq!(parse_eth_address(&address.to_lowercase()), Ok(bytes));
+        }
+
+        assert!(parse_eth_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(parse_eth_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed00").is_err());
+    }
+
+    #[test]
+    fn test_to_eth_address() {
+        // the address of the private key 1
+        let point = (AffinePoint::GENERATOR * Scalar::ONE).to_affine();
+        assert_eq!(
+            to_eth_address(&point),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+/// Parses the `linkabilityFlag` field of a stringified signature:
+/// the TS libraries write an empty string when no flag was used
+pub fn parse_linkability_flag(flag: String) -> Option<String> {
+    (!flag.is_empty()).then_some(flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_linkability_flag() {
+        assert_eq!(parse_linkability_flag(String::new()), None);
+        assert_eq!(
+            parse_linkability_flag("flag".to_string()),
+            Some("flag".to_string())
+        );
+    }
+}
@@ -1,8 +1,14 @@
+pub mod base64_json;
+pub mod curve;
+pub mod eth_address;
 pub mod hash_to_secp256k1;
 pub mod hex_to_decimal;
 pub mod keccak256;
+pub mod linkability_flag;
 pub mod scalar_from_hex;
 pub mod scalar_to_string;
 pub mod serialize_point;
 pub mod serialize_ring;
+pub mod signature_config;
+pub mod sort_ring;
 pub mod test_utils;
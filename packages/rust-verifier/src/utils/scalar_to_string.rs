@@ -1,6 +1,13 @@
-use k256::Scalar;
+use super::curve::RingCurve;
+use k256::{Scalar, Secp256k1};
 
 pub fn scalar_to_string(scalar: &Scalar) -> String {
     let scalar_bytes = scalar.to_bytes();
     hex::encode(scalar_bytes)
 }
+
+/// Converts a scalar to a hex string without leading zeros,
+/// as `bigint.toString(16)` does in the TS libraries
+pub fn scalar_to_trimmed_string(scalar: &Scalar) -> String {
+    Secp256k1::scalar_to_hex(scalar)
+}
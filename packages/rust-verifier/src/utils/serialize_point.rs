@@ -1,48 +1,257 @@
+use super::curve::{Curve, RingCurve};
 use elliptic_curve::sec1::FromEncodedPoint;
 use hex::{self, FromHex};
-use k256::{elliptic_curve::sec1::ToEncodedPoint, AffinePoint, EncodedPoint};
+use k256::{elliptic_curve::sec1::ToEncodedPoint, AffinePoint, EncodedPoint, Scalar, Secp256k1};
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
 
 /// Serializes an AffinePoint to a compressed hexadecimal string
 pub fn serialize_point(point: AffinePoint) -> String {
-    // Encode the point in uncompressed form to access x and y coordinates
-    let encoded = point.to_encoded_point(false); // false for uncompressed
+    Secp256k1::serialize_point(&point)
+}
 
-    // Get x and y coordinate bytes
-    let x_bytes = encoded.x().expect("x coordinate missing");
-    let y_bytes = encoded.y().expect("y coordinate missing");
+/// Deserialize a compressed hexadecimal string to an AffinePoint
+///
+/// Use `parse_public_key` to accept other public key encodings.
+pub fn deserialize_point(hex_str: String) -> Result<AffinePoint, String> {
+    Secp256k1::deserialize_point(&hex_str)
+}
 
-    // Convert x-coordinate bytes to a hexadecimal string
-    let x_hex = hex::encode(x_bytes);
+/// The formats a public key can be converted to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicKeyFormat {
+    /// 33-byte compressed hex (`02`/`03` prefix), as used in the signatures
+    Compressed,
+    /// 65-byte uncompressed hex (`04` prefix)
+    Uncompressed,
+    /// `0x`-prefixed 64-byte `x || y` hex, as used by Ethereum
+    Ethereum,
+    /// `["x","y"]` decimal coordinates
+    Coordinates,
+    /// The JSON produced by `Point.toString()` in the TS libraries
+    PointJson,
+    /// 32-byte x-only hex, as used by BIP340 and Taproot.
+    /// The y parity is lost: parsing it back gives the point with an even y.
+    XOnly,
+}
 
-    // Pad the x-coordinate hex string to 64 characters with leading zeros
-    let x_hex_padded = format!("{:0>64}", x_hex);
+/// The JSON representation of a point, as produced by `Point.toString()` in the TS libraries
+#[derive(Serialize, Deserialize)]
+struct StringifiedPoint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    curve: Option<String>,
+    x: String,
+    y: String,
+}
 
-    // Determine if the y-coordinate is even or odd
-    // Since y_bytes are in big-endian order, check the least significant bit of the last byte
-    let y_is_even = (y_bytes[y_bytes.len() - 1] & 1) == 0;
+/// Parses a public key from any of the supported encodings:
+/// - compressed (`02`/`03`) or uncompressed (`04`) hex, with or without `0x` prefix
+/// - 64-byte `x || y` hex, as used by Ethereum
+/// - 32-byte x-only hex (BIP340), lifted to the point with an even y
+/// - `["x", "y"]` decimal coordinates
+/// - `{"x": "...", "y": "..."}` decimal coordinates, as produced by `Point.toString()` in the TS libraries
+pub fn parse_public_key(public_key: &str) -> Result<AffinePoint, String> {
+    let public_key = public_key.trim();
 
-    // Set the prefix based on the parity of the y-coordinate
-    let prefix = if y_is_even { "02" } else { "03" };
+    if public_key.starts_with('[') {
+        let coordinates: Vec<String> =
+            serde_json::from_str(public_key).map_err(|e| format!("Invalid coordinates: {}", e))?;
+        if coordinates.len() != 2 {
+            return Err("Coordinates must be a [x, y] pair".to_string());
+        }
+        return point_from_coordinates(&coordinates[0], &coordinates[1]);
+    }
+
+    if public_key.starts_with('{') {
+        let point: StringifiedPoint =
+            serde_json::from_str(public_key).map_err(|e| format!("Invalid point JSON: {}", e))?;
+        if let Some(curve) = point.curve {
+            if Curve::from_json_string(&curve).or_else(|_| Curve::from_name(&curve))?
+                != Curve::Secp256k1
+            {
+                return Err(format!("Unsupported curve: {}", curve));
+            }
+        }
+        return point_from_coordinates(&point.x, &point.y);
+    }
 
-    format!("{}{}", prefix, x_hex_padded)
+    let hex_str = public_key
+        .strip_prefix("0x")
+        .or_else(|| public_key.strip_prefix("0X"))
+        .unwrap_or(public_key);
+    let bytes = Vec::from_hex(hex_str).map_err(|_| "Invalid hexadecimal string".to_string())?;
+
+    parse_public_key_bytes(&bytes)
 }
 
-/// Deserialize a compressed hexadecimal string to an AffinePoint
-pub fn deserialize_point(hex_str: String) -> Result<AffinePoint, String> {
-    // Step 1: Convert the hexadecimal string to bytes
-    let bytes = match Vec::from_hex(hex_str) {
-        Ok(b) => b,
-        Err(_) => return Err("Invalid hexadecimal string".to_string()),
-    };
+/// Parses a public key from its compressed (33 bytes), uncompressed (65 bytes),
+/// raw `x || y` (64 bytes) or x-only (32 bytes) encoding
+pub fn parse_public_key_bytes(bytes: &[u8]) -> Result<AffinePoint, String> {
+    let encoded_point = match bytes.len() {
+        32 => return lift_x_only(bytes),
+        33 if bytes[0] == 0x02 || bytes[0] == 0x03 => EncodedPoint::from_bytes(bytes),
+        65 if bytes[0] == 0x04 => EncodedPoint::from_bytes(bytes),
+        64 => Ok(EncodedPoint::from_untagged_bytes(bytes.into())),
+        33 | 65 => return Err("Invalid public key prefix".to_string()),
+        length => {
+            return Err(format!(
+                "Invalid public key length: expected 32, 33, 64 or 65 bytes, got {}",
+                length
+            ))
+        }
+    }
+    .map_err(|_| "Invalid public key encoding".to_string())?;
+
+    Option::from(AffinePoint::from_encoded_point(&encoded_point))
+        .ok_or_else(|| "Point is not on the curve".to_string())
+}
+
+/// Lifts a 32-byte x-only public key to the point with an even y, as BIP340 does
+pub fn lift_x_only(x: &[u8]) -> Result<AffinePoint, String> {
+    if x.len() != 32 {
+        return Err("An x-only public key must be 32 bytes long".to_string());
+    }
+
+    let mut compressed = [0x02; 33];
+    compressed[1..].copy_from_slice(x);
+    let encoded_point = EncodedPoint::from_bytes(compressed)
+        .map_err(|_| "Invalid x-only public key".to_string())?;
+
+    Option::from(AffinePoint::from_encoded_point(&encoded_point))
+        .ok_or_else(|| "Point is not on the curve".to_string())
+}
+
+/// Negates a private key if its public key has an odd y, as BIP340 does, so that its public
+/// key is the point its x-only public key is lifted to (see `lift_x_only`)
+pub fn even_y_private_key(private_key: Scalar) -> Scalar {
+    let public_key = (AffinePoint::GENERATOR * private_key).to_affine();
+    if public_key.to_encoded_point(true).as_bytes()[0] == 0x03 {
+        -private_key
+    } else {
+        private_key
+    }
+}
+
+/// Builds a point from its decimal coordinates
+pub fn point_from_coordinates(x: &str, y: &str) -> Result<AffinePoint, String> {
+    let x_bytes = coordinate_to_bytes(x)?;
+    let y_bytes = coordinate_to_bytes(y)?;
+
+    let encoded_point =
+        EncodedPoint::from_affine_coordinates(&x_bytes.into(), &y_bytes.into(), false);
+
+    // coordinates greater than the field modulus are rejected here
+    Option::from(AffinePoint::from_encoded_point(&encoded_point))
+        .ok_or_else(|| "Point is not on the curve".to_string())
+}
 
-    if bytes.len() != 33 {
-        return Err("Invalid length for a compressed point".to_string());
+/// Converts a public key to the given format
+pub fn format_public_key(point: &AffinePoint, format: PublicKeyFormat) -> String {
+    let encoded = point.to_encoded_point(false);
+    let x = encoded.x().expect("x coordinate missing");
+    let y = encoded.y().expect("y coordinate missing");
+
+    match format {
+        PublicKeyFormat::Compressed => serialize_point(*point),
+        PublicKeyFormat::Uncompressed => hex::encode(encoded.as_bytes()),
+        PublicKeyFormat::Ethereum => format!("0x{}{}", hex::encode(x), hex::encode(y)),
+        PublicKeyFormat::Coordinates => serde_json::to_string(&[
+            BigUint::from_bytes_be(x).to_string(),
+            BigUint::from_bytes_be(y).to_string(),
+        ])
+        .unwrap(),
+        PublicKeyFormat::PointJson => serde_json::to_string(&StringifiedPoint {
+            curve: Some(Curve::Secp256k1.to_json_string()),
+            x: BigUint::from_bytes_be(x).to_string(),
+            y: BigUint::from_bytes_be(y).to_string(),
+        })
+        .unwrap(),
+        PublicKeyFormat::XOnly => hex::encode(x),
     }
+}
 
-    let encoded_point = EncodedPoint::from_bytes(&bytes)
-        .map_err(|_| "Invalid compressed point encoding".to_string())?;
+fn coordinate_to_bytes(coordinate: &str) -> Result<[u8; 32], String> {
+    let value = BigUint::from_str_radix(coordinate.trim(), 10)
+        .map_err(|_| format!("Invalid decimal coordinate: {}", coordinate))?;
 
-    let affine_point = AffinePoint::from_encoded_point(&encoded_point);
+    let value_bytes = value.to_bytes_be();
+    if value_bytes.len() > 32 {
+        return Err("Coordinate must be lower than the field modulus".to_string());
+    }
+
+    let mut bytes = [0u8; 32];
+    bytes[32 - value_bytes.len()..].copy_from_slice(&value_bytes);
+    Ok(bytes)
+}
 
-    Ok(affine_point.unwrap())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: &str = "55066263022277343669578718895168534326250603453777594175500187360389116729240";
+    const Y: &str = "32670510020758816978083085130507043184471273380659243275938904335757337482424";
+
+    #[test]
+    fn test_parse_public_key_formats() {
+        let g = AffinePoint::GENERATOR;
+
+        for format in [
+            PublicKeyFormat::Compressed,
+            PublicKeyFormat::Uncompressed,
+            PublicKeyFormat::Ethereum,
+            PublicKeyFormat::Coordinates,
+            PublicKeyFormat::PointJson,
+        ] {
+            let formatted = format_public_key(&g, format);
+            assert_eq!(parse_public_key(&formatted), Ok(g), "{:?}", format);
+        }
+
+        assert_eq!(
+            format_public_key(&g, PublicKeyFormat::Coordinates),
+            format!(r#"["{}","{}"]"#, X, Y)
+        );
+        assert_eq!(
+            parse_public_key(&format!(r#"{{"x":"{}","y":"{}"}}"#, X, Y)),
+            Ok(g)
+        );
+        assert_eq!(
+            parse_public_key(&format!("0x{}", serialize_point(g))),
+            Ok(g)
+        );
+    }
+
+    #[test]
+    fn test_x_only_public_keys() {
+        let g = AffinePoint::GENERATOR;
+        let x_only = format_public_key(&g, PublicKeyFormat::XOnly);
+        assert_eq!(x_only.len(), 64);
+        assert_eq!(parse_public_key(&x_only), Ok(g));
+
+        // the x-only key of a point with an odd y is lifted to its negation
+        let odd_point = -g;
+        assert_eq!(
+            parse_public_key(&format_public_key(&odd_point, PublicKeyFormat::XOnly)),
+            Ok(g)
+        );
+        assert!(lift_x_only(&[0u8; 31]).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_public_keys() {
+        assert!(parse_public_key("not a key").is_err());
+        assert!(parse_public_key("0x1234").is_err());
+        // wrong prefix
+        assert!(parse_public_key(&format!(
+            "05{}",
+            &serialize_point(AffinePoint::GENERATOR)[2..]
+        ))
+        .is_err());
+        // not on the curve
+        assert!(parse_public_key(&format!(r#"["{}","1"]"#, X)).is_err());
+        assert!(parse_public_key(r#"["1"]"#).is_err());
+        assert!(
+            parse_public_key(&format!(r#"{{"curve":"ED25519","x":"{}","y":"{}"}}"#, X, Y)).is_err()
+        );
+    }
 }
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// The hash functions supported by the Alice's Ring libraries
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashFunction {
+    #[serde(rename = "keccak256")]
+    Keccak256,
+    #[serde(rename = "sha512")]
+    Sha512,
+    #[serde(rename = "sha256")]
+    Sha256,
+}
+
+impl HashFunction {
+    /// Returns the hash function name as used by the TS libraries
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashFunction::Keccak256 => "keccak256",
+            HashFunction::Sha512 => "sha512",
+            HashFunction::Sha256 => "sha256",
+        }
+    }
+
+    /// Parses a hash function from its name (e.g. `keccak256`)
+    pub fn from_name(name: &str) -> Result<HashFunction, String> {
+        match name {
+            "keccak256" => Ok(HashFunction::Keccak256),
+            "sha512" => Ok(HashFunction::Sha512),
+            "sha256" => Ok(HashFunction::Sha256),
+            _ => Err(format!("Unsupported hash function: {}", name)),
+        }
+    }
+}
+
+/// The optional config attached to a signature by the TS libraries
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evm_compatibility: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<HashFunction>,
+}
@@ -0,0 +1,20 @@
+use super::curve::RingCurve;
+use core::cmp::Ordering;
+use k256::{AffinePoint, Secp256k1};
+
+/// Compares two points by x coordinate, then by y coordinate if the x's are equal
+pub fn compare_points(a: &AffinePoint, b: &AffinePoint) -> Ordering {
+    Secp256k1::compare_points(a, b)
+}
+
+/// Sorts a ring by x ascending coordinate (and y ascending if x's are equal),
+/// as `sortRing` does in the TS libraries
+pub fn sort_ring(ring: &mut [AffinePoint]) {
+    ring.sort_by(compare_points);
+}
+
+/// Checks if a ring is sorted by x ascending coordinate (and y ascending if x's are equal)
+pub fn is_ring_sorted(ring: &[AffinePoint]) -> bool {
+    ring.windows(2)
+        .all(|pair| compare_points(&pair[0], &pair[1]) != Ordering::Greater)
+}
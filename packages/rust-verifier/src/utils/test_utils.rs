@@ -1,63 +1,51 @@
-use elliptic_curve::sec1::FromEncodedPoint;
-use k256::{AffinePoint, EncodedPoint};
-use num_bigint::BigUint;
-use num_traits::Num;
+use super::curve::RingCurve;
+use super::serialize_point::point_from_coordinates;
+use crate::ring::Ring;
+use k256::{AffinePoint, Scalar, Secp256k1};
+use std::path::{Path, PathBuf};
 
 pub fn get_ring(points: &[(&str, &str)]) -> Vec<AffinePoint> {
-    // Convert coordinate strings to BigUint
-    let points_biguint: Vec<(BigUint, BigUint)> = points
+    // Convert decimal coordinates to AffinePoint instances
+    points
         .iter()
-        .map(|(x_str, y_str)| {
-            let x = BigUint::from_str_radix(x_str, 10).unwrap();
-            let y = BigUint::from_str_radix(y_str, 10).unwrap();
-            (x, y)
-        })
-        .collect();
-
-    // Helper function to get the field modulus
-    fn get_field_modulus() -> BigUint {
-        // secp256k1 field modulus p = 2^256 - 2^32 - 977
-        let p_hex = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
-        BigUint::from_str_radix(p_hex, 16).unwrap()
-    }
-
-    fn biguint_to_32bytes(n: &BigUint) -> [u8; 32] {
-        let n_bytes = n.to_bytes_be();
-        let mut bytes = [0u8; 32];
-        let len = n_bytes.len();
-        if len > 32 {
-            // Truncate to the last 32 bytes
-            bytes.copy_from_slice(&n_bytes[len - 32..]);
-        } else {
-            // Pad with zeros on the left
-            bytes[32 - len..].copy_from_slice(&n_bytes);
-        }
-        bytes
-    }
+        .map(|(x_str, y_str)| point_from_coordinates(x_str, y_str).unwrap())
+        .collect()
+}
 
-    // Convert BigUint coordinates to AffinePoint instances
-    points_biguint
+/// Returns the public keys of small private keys (e.g. `&[2, 3, 4]`), in the same order
+pub fn get_points(private_keys: &[u64]) -> Vec<AffinePoint> {
+    private_keys
         .iter()
-        .map(|(x_biguint, y_biguint)| {
-            let field_modulus = get_field_modulus();
+        .map(|k| Secp256k1::mul_generator(&Scalar::from(*k)))
+        .collect()
+}
 
-            // Reduce x and y modulo the field modulus
-            let x_mod = x_biguint.clone() % &field_modulus;
-            let y_mod = y_biguint.clone() % &field_modulus;
+/// Returns the ring of the public keys of small private keys, in canonical order
+pub fn get_sorted_ring(private_keys: &[u64]) -> Ring {
+    Ring::new(get_points(private_keys)).unwrap()
+}
 
-            // Convert BigUint to 32-byte arrays
-            let x_bytes = biguint_to_32bytes(&x_mod);
-            let y_bytes = biguint_to_32bytes(&y_mod);
+/// A temporary directory unique to a test, removed when dropped (even if the test fails)
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(test_name: &str) -> TestDir {
+        let path = std::env::temp_dir().join(format!(
+            "ring_signature_verifier_{}_{}",
+            std::process::id(),
+            test_name
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
 
-            // Create an EncodedPoint with uncompressed form
-            let encoded_point = EncodedPoint::from_affine_coordinates(
-                &x_bytes.into(),
-                &y_bytes.into(),
-                /* compress = */ false,
-            );
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
 
-            // Convert EncodedPoint to AffinePoint
-            AffinePoint::from_encoded_point(&encoded_point).unwrap()
-        })
-        .collect()
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }